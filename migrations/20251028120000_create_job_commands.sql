@@ -0,0 +1,7 @@
+-- Persist the commands of each job, preserving their order
+CREATE TABLE job_commands (
+    job_id BIGINT NOT NULL REFERENCES jobs(id) ON UPDATE CASCADE ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    command TEXT NOT NULL,
+    PRIMARY KEY (job_id, position)
+);
@@ -0,0 +1,12 @@
+-- Turn jobs_status_updates into an append-only history with many updates per job
+ALTER TABLE jobs_status_updates
+DROP CONSTRAINT jobs_status_updates_pkey;
+
+ALTER TABLE jobs_status_updates
+ADD COLUMN id BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY;
+
+-- Why the job state machine rejected an update (NULL when the update was accepted)
+ALTER TABLE jobs_status_updates
+ADD COLUMN rejection_reason TEXT;
+
+CREATE INDEX jobs_status_updates_job_id_idx ON jobs_status_updates (job_id, id);
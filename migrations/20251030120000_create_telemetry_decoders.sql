@@ -0,0 +1,6 @@
+-- Declarative telemetry decoder definition for each satellite
+CREATE TABLE telemetry_decoders (
+    sat_id BIGINT PRIMARY KEY REFERENCES satellites(id) ON UPDATE CASCADE ON DELETE CASCADE,
+    definition JSONB NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now()
+);
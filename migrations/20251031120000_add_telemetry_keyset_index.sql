@@ -0,0 +1,2 @@
+-- Keyset pagination walks a satellite's telemetry in (timestamp, id) order
+CREATE INDEX telemetry_sat_id_timestamp_id_idx ON telemetry (sat_id, timestamp, id);
@@ -0,0 +1,31 @@
+-- Limits checked against every ingested telemetry value of a satellite parameter
+CREATE TABLE telemetry_limits (
+    sat_id BIGINT NOT NULL REFERENCES satellites(id) ON UPDATE CASCADE ON DELETE CASCADE,
+    parameter TEXT NOT NULL,
+    red_low DOUBLE PRECISION,
+    yellow_low DOUBLE PRECISION,
+    yellow_high DOUBLE PRECISION,
+    red_high DOUBLE PRECISION,
+    -- Consecutive violating samples required before an alarm is raised
+    persistence INTEGER NOT NULL DEFAULT 1 CHECK (persistence >= 1),
+    PRIMARY KEY (sat_id, parameter)
+);
+
+CREATE TYPE alarm_severity AS ENUM ('Yellow', 'Red');
+
+-- An alarm is active until cleared_at is set, and acknowledged independently of clearing
+CREATE TABLE alarms (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    sat_id BIGINT NOT NULL REFERENCES satellites(id) ON UPDATE CASCADE ON DELETE CASCADE,
+    parameter TEXT NOT NULL,
+    severity alarm_severity NOT NULL,
+    value DOUBLE PRECISION NOT NULL,
+    raised_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    cleared_at TIMESTAMP WITH TIME ZONE,
+    acknowledged_at TIMESTAMP WITH TIME ZONE,
+    acknowledged_by TEXT
+);
+
+-- At most one active alarm per satellite parameter
+CREATE UNIQUE INDEX alarms_active_idx ON alarms (sat_id, parameter) WHERE cleared_at IS NULL;
+CREATE INDEX alarms_raised_at_idx ON alarms (raised_at DESC);
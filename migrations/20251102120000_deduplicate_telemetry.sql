@@ -0,0 +1,21 @@
+-- Hash identifying a frame regardless of the station that received it
+ALTER TABLE telemetry
+ADD COLUMN frame_hash BYTEA;
+
+CREATE INDEX telemetry_sat_id_frame_hash_idx ON telemetry (sat_id, frame_hash, timestamp);
+
+-- Every station that received a frame. telemetry.gs_id keeps the first one.
+CREATE TABLE telemetry_receptions (
+    telemetry_id BIGINT NOT NULL REFERENCES telemetry(id) ON DELETE CASCADE,
+    gs_id BIGINT NOT NULL,
+    received_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    rssi DOUBLE PRECISION,
+    snr DOUBLE PRECISION,
+    frequency_offset DOUBLE PRECISION,
+    PRIMARY KEY (telemetry_id, gs_id)
+);
+
+CREATE INDEX telemetry_receptions_gs_id_idx ON telemetry_receptions (gs_id);
+
+INSERT INTO telemetry_receptions (telemetry_id, gs_id, received_at)
+SELECT id, gs_id, timestamp FROM telemetry;
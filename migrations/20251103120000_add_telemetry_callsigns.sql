@@ -0,0 +1,4 @@
+-- AX.25 addresses of frames received as raw link-layer frames
+ALTER TABLE telemetry
+ADD COLUMN source_callsign TEXT,
+ADD COLUMN destination_callsign TEXT;
@@ -0,0 +1,19 @@
+-- CCSDS transfer frame configuration of satellites using CCSDS framing
+ALTER TABLE satellites
+ADD COLUMN ccsds_spacecraft_id INTEGER,
+ADD COLUMN ccsds_fecf BOOLEAN NOT NULL DEFAULT true;
+
+-- Space packet identification of telemetry extracted from CCSDS frames
+ALTER TABLE telemetry
+ADD COLUMN apid INTEGER,
+ADD COLUMN sequence_count INTEGER;
+
+-- Decoders may be attached to a single APID. A NULL APID is the satellite's default decoder.
+ALTER TABLE telemetry_decoders
+ADD COLUMN apid INTEGER;
+
+ALTER TABLE telemetry_decoders
+DROP CONSTRAINT telemetry_decoders_pkey;
+
+ALTER TABLE telemetry_decoders
+ADD CONSTRAINT telemetry_decoders_sat_id_apid_key UNIQUE NULLS NOT DISTINCT (sat_id, apid);
@@ -0,0 +1,15 @@
+-- Inbound MQTT messages that failed parsing, decoding or persistence, kept for
+-- inspection and replay
+CREATE TABLE dead_letters (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    topic TEXT NOT NULL,
+    payload BYTEA NOT NULL,
+    -- Reason of the latest failure, replaced by failed replays
+    error TEXT NOT NULL,
+    received_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    replay_attempts INTEGER NOT NULL DEFAULT 0,
+    replayed_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX dead_letters_received_at_idx ON dead_letters (received_at DESC);
+CREATE INDEX dead_letters_pending_idx ON dead_letters (received_at) WHERE replayed_at IS NULL;
@@ -0,0 +1,14 @@
+-- Latest heartbeat of each ground station. A station is online while its latest
+-- heartbeat is recent enough.
+CREATE TABLE ground_station_heartbeats (
+    gs_id BIGINT PRIMARY KEY REFERENCES ground_stations(id) ON UPDATE CASCADE ON DELETE CASCADE,
+    -- Time the station sent the heartbeat, by its own clock
+    timestamp TIMESTAMP WITH TIME ZONE NOT NULL,
+    -- Time the API received it, which the offline timeout is measured from
+    received_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    software_version TEXT,
+    uptime_secs BIGINT,
+    rotator_status TEXT,
+    radio_status TEXT,
+    disk_free_bytes BIGINT
+);
@@ -0,0 +1,2 @@
+ALTER TABLE telemetry_receptions
+    ADD CONSTRAINT telemetry_receptions_gs_id_fkey FOREIGN KEY (gs_id) REFERENCES ground_stations(id) ON UPDATE CASCADE ON DELETE CASCADE;
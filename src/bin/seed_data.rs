@@ -16,9 +16,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut current_time = now - Duration::hours(24);
 
     for i in 0..100 {
-        // Create a simple payload (in real scenario this would be encoded telemetry data)
+        // Frames use the same key:value format ground stations publish over MQTT
         let payload = format!(
-            "temp:{},volt:{},curr:{},batt:{}",
+            "type:telemetry|sat_id:1|gs_id:1|timestamp:{}|temperature:{}|voltage:{}|current:{}|battery_level:{}",
+            current_time.timestamp(),
             20.0 + (i as f32 * 0.1),  // Varying temperature
             12.0 + (i as f32 * 0.01), // Varying voltage
             1.0 + (i as f32 * 0.005), // Varying current
@@ -26,17 +27,29 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         )
         .into_bytes();
 
-        sqlx::query!(
+        let id: i64 = sqlx::query_scalar(
             r#"
-            INSERT INTO telemetry (id, timestamp, sat_id, gs_id, payload)
-            VALUES ($1, $2, $3, $4, $5)
+            INSERT INTO telemetry (timestamp, sat_id, gs_id, payload)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id
             "#,
-            i as i64, // Use sequential IDs
-            current_time,
-            1i64, // Default satellite ID
-            1i64, // Default ground station ID
-            &payload
         )
+        .bind(current_time)
+        .bind(1i64) // Default satellite ID
+        .bind(1i64) // Default ground station ID
+        .bind(&payload)
+        .fetch_one(&pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO telemetry_receptions (telemetry_id, gs_id, received_at)
+            VALUES ($1, $2, $3)
+            "#,
+        )
+        .bind(id)
+        .bind(1i64)
+        .bind(current_time)
         .execute(&pool)
         .await?;
 
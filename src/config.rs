@@ -19,6 +19,182 @@ pub struct MessageBrokerConfig {
     pub host: String,
     pub port: u16,
     pub keep_alive: u32,
+    #[serde(default)]
+    pub username: Option<String>,
+    /// Never included in serialized configuration
+    #[serde(default, skip_serializing)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub tls: BrokerTlsConfig,
+    #[serde(default)]
+    pub topics: TopicsConfig,
+}
+
+/// TLS for the broker connection. Certificates and keys are PEM file paths.
+#[derive(Debug, Deserialize, Serialize, Clone, Default, ToSchema)]
+pub struct BrokerTlsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// CA certificate the broker's certificate is verified against. The platform's
+    /// root certificates are used when unset.
+    #[serde(default)]
+    pub ca: Option<String>,
+    /// Client certificate, for brokers requiring mutual TLS
+    #[serde(default)]
+    pub cert: Option<String>,
+    /// Private key of the client certificate
+    #[serde(default)]
+    pub key: Option<String>,
+}
+
+/// MQTT topic patterns. `{gs_id}` and `{sat_id}` stand for a whole topic level.
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
+pub struct TopicsConfig {
+    /// Frames a ground station received from a satellite
+    #[serde(default = "default_telemetry_topic")]
+    pub telemetry: String,
+    /// Status reports of a ground station, such as job status updates
+    #[serde(default = "default_status_topic")]
+    pub status: String,
+    /// Job descriptions sent to a ground station
+    #[serde(default = "default_jobs_topic")]
+    pub jobs: String,
+    /// Commands sent to a ground station
+    #[serde(default = "default_commands_topic")]
+    pub commands: String,
+    /// Retained `online`/`offline` state of the API, with no placeholders
+    #[serde(default = "default_presence_topic")]
+    pub presence: String,
+}
+
+impl Default for TopicsConfig {
+    fn default() -> Self {
+        Self {
+            telemetry: default_telemetry_topic(),
+            status: default_status_topic(),
+            jobs: default_jobs_topic(),
+            commands: default_commands_topic(),
+            presence: default_presence_topic(),
+        }
+    }
+}
+
+fn default_telemetry_topic() -> String {
+    "stations/{gs_id}/telemetry/{sat_id}".into()
+}
+
+fn default_status_topic() -> String {
+    "stations/{gs_id}/status".into()
+}
+
+fn default_jobs_topic() -> String {
+    "stations/{gs_id}/jobs".into()
+}
+
+fn default_commands_topic() -> String {
+    "stations/{gs_id}/commands".into()
+}
+
+fn default_presence_topic() -> String {
+    "api/presence".into()
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
+pub struct JobsConfig {
+    /// How far ahead (in hours) to search for a pass when scheduling a job
+    #[serde(default = "default_pass_horizon_hours")]
+    pub pass_horizon_hours: u32,
+    /// Minimum peak elevation (in degrees) for a pass to be schedulable
+    #[serde(default)]
+    pub min_elevation: f64,
+}
+
+impl Default for JobsConfig {
+    fn default() -> Self {
+        Self {
+            pass_horizon_hours: default_pass_horizon_hours(),
+            min_elevation: 0.0,
+        }
+    }
+}
+
+fn default_pass_horizon_hours() -> u32 {
+    48
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
+pub struct TelemetryConfig {
+    /// Frames of a satellite with the same content whose timestamps differ by at most
+    /// this many seconds are stored once
+    #[serde(default = "default_duplicate_tolerance_secs")]
+    pub duplicate_tolerance_secs: u32,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            duplicate_tolerance_secs: default_duplicate_tolerance_secs(),
+        }
+    }
+}
+
+fn default_duplicate_tolerance_secs() -> u32 {
+    10
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
+pub struct GroundStationsConfig {
+    /// A station is offline when no heartbeat was received from it for this many seconds
+    #[serde(default = "default_offline_timeout_secs")]
+    pub offline_timeout_secs: u32,
+}
+
+impl Default for GroundStationsConfig {
+    fn default() -> Self {
+        Self {
+            offline_timeout_secs: default_offline_timeout_secs(),
+        }
+    }
+}
+
+fn default_offline_timeout_secs() -> u32 {
+    120
+}
+
+/// Queue between the MQTT connection and the writer storing inbound messages
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
+pub struct IngestConfig {
+    /// Messages waiting to be stored before new ones are dropped
+    #[serde(default = "default_queue_capacity")]
+    pub queue_capacity: usize,
+    /// Most messages stored in one database transaction
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+    /// Longest a message waits for its batch to fill, in milliseconds
+    #[serde(default = "default_flush_interval_ms")]
+    pub flush_interval_ms: u64,
+}
+
+impl Default for IngestConfig {
+    fn default() -> Self {
+        Self {
+            queue_capacity: default_queue_capacity(),
+            batch_size: default_batch_size(),
+            flush_interval_ms: default_flush_interval_ms(),
+        }
+    }
+}
+
+fn default_queue_capacity() -> usize {
+    10000
+}
+
+fn default_batch_size() -> usize {
+    100
+}
+
+fn default_flush_interval_ms() -> u64 {
+    200
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
@@ -26,6 +202,14 @@ pub struct Config {
     pub server: ServerConfig,
     pub database: DatabaseConfig,
     pub message_broker: MessageBrokerConfig,
+    #[serde(default)]
+    pub jobs: JobsConfig,
+    #[serde(default)]
+    pub telemetry: TelemetryConfig,
+    #[serde(default)]
+    pub ingest: IngestConfig,
+    #[serde(default)]
+    pub ground_stations: GroundStationsConfig,
 }
 
 impl Config {
@@ -0,0 +1,274 @@
+use crate::models::entities::{DecoderField, Endianness, TelemetryDecoder};
+use std::collections::HashSet;
+
+/// A parameter extracted from a frame. `value` is `None` when the field lies
+/// outside the frame, and `valid` is false when it is missing or out of its valid range.
+#[derive(Debug, Clone)]
+pub struct DecodedParameter {
+    pub name: String,
+    pub value: Option<f64>,
+    pub unit: Option<String>,
+    pub valid: bool,
+}
+
+/// Check that a decoder definition can be applied to frames
+pub fn validate(decoder: &TelemetryDecoder) -> Result<(), String> {
+    if decoder.fields.is_empty() {
+        return Err("Decoder must define at least one field".into());
+    }
+
+    let mut names = HashSet::new();
+    for field in &decoder.fields {
+        if field.name.trim().is_empty() {
+            return Err("Field names cannot be empty".into());
+        }
+        if !names.insert(field.name.as_str()) {
+            return Err(format!("Duplicate field name '{}'", field.name));
+        }
+        if !matches!(field.width, 1 | 2 | 4 | 8) {
+            return Err(format!(
+                "Field '{}' has width {}, expected 1, 2, 4 or 8 bytes",
+                field.name, field.width
+            ));
+        }
+        if !field.scale.is_finite() || !field.offset.is_finite() {
+            return Err(format!(
+                "Field '{}' must have a finite scale and offset",
+                field.name
+            ));
+        }
+        if let (Some(min), Some(max)) = (field.valid_min, field.valid_max) {
+            if min > max {
+                return Err(format!(
+                    "Field '{}' has validMin greater than validMax",
+                    field.name
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Decode every field of the decoder from a raw frame
+pub fn decode(decoder: &TelemetryDecoder, payload: &[u8]) -> Vec<DecodedParameter> {
+    decoder
+        .fields
+        .iter()
+        .map(|field| {
+            let value = decode_field(field, payload);
+            DecodedParameter {
+                name: field.name.clone(),
+                value,
+                unit: field.unit.clone(),
+                valid: value.is_some_and(|v| in_range(field, v)),
+            }
+        })
+        .collect()
+}
+
+fn decode_field(field: &DecoderField, payload: &[u8]) -> Option<f64> {
+    let width = field.width as usize;
+    let bytes = payload.get(field.byte_offset..field.byte_offset.checked_add(width)?)?;
+
+    let raw = match field.endianness {
+        Endianness::Big => bytes.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64),
+        Endianness::Little => bytes
+            .iter()
+            .rev()
+            .fold(0u64, |acc, b| (acc << 8) | *b as u64),
+    };
+
+    let raw = if field.signed {
+        // Sign-extend from the field width to 64 bits
+        let shift = 64 - 8 * width as u32;
+        (((raw << shift) as i64) >> shift) as f64
+    } else {
+        raw as f64
+    };
+
+    Some(raw * field.scale + field.offset)
+}
+
+fn in_range(field: &DecoderField, value: f64) -> bool {
+    !field.valid_min.is_some_and(|min| value < min)
+        && !field.valid_max.is_some_and(|max| value > max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(byte_offset: usize, width: u8, endianness: Endianness, signed: bool) -> DecoderField {
+        DecoderField {
+            name: "value".into(),
+            byte_offset,
+            width,
+            endianness,
+            signed,
+            scale: 1.0,
+            offset: 0.0,
+            unit: None,
+            valid_min: None,
+            valid_max: None,
+        }
+    }
+
+    fn decoder(fields: Vec<DecoderField>) -> TelemetryDecoder {
+        TelemetryDecoder { fields }
+    }
+
+    #[test]
+    fn decodes_every_width_and_byte_order() {
+        let payload = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
+        let cases = [
+            (1, Endianness::Big, 0x01u64),
+            (1, Endianness::Little, 0x01),
+            (2, Endianness::Big, 0x0102),
+            (2, Endianness::Little, 0x0201),
+            (4, Endianness::Big, 0x0102_0304),
+            (4, Endianness::Little, 0x0403_0201),
+            (8, Endianness::Big, 0x0102_0304_0506_0708),
+            (8, Endianness::Little, 0x0807_0605_0403_0201),
+        ];
+        for (width, endianness, expected) in cases {
+            let f = field(0, width, endianness, false);
+            assert_eq!(
+                decode_field(&f, &payload),
+                Some(expected as f64),
+                "width {} {:?}",
+                width,
+                endianness
+            );
+        }
+    }
+
+    #[test]
+    fn sign_extends_signed_fields() {
+        let payload = [0xFF, 0xFE, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF];
+        let cases = [
+            (1, Endianness::Big, -1.0),
+            (2, Endianness::Big, -2.0),
+            (2, Endianness::Little, -257.0),
+            (4, Endianness::Big, -65537.0),
+            (8, Endianness::Little, -257.0),
+        ];
+        for (width, endianness, expected) in cases {
+            let f = field(0, width, endianness, true);
+            assert_eq!(
+                decode_field(&f, &payload),
+                Some(expected),
+                "width {} {:?}",
+                width,
+                endianness
+            );
+        }
+
+        let unsigned = field(0, 1, Endianness::Big, false);
+        assert_eq!(decode_field(&unsigned, &payload), Some(255.0));
+    }
+
+    #[test]
+    fn applies_scale_and_offset() {
+        let f = DecoderField {
+            scale: 0.5,
+            offset: -10.0,
+            ..field(1, 2, Endianness::Big, false)
+        };
+        // 0x0064 = 100
+        assert_eq!(decode_field(&f, &[0xAA, 0x00, 0x64]), Some(40.0));
+    }
+
+    #[test]
+    fn reports_fields_outside_the_payload() {
+        let cases = [
+            (0, 2, vec![0x01]),
+            (2, 1, vec![0x01, 0x02]),
+            (4, 4, vec![0x00; 7]),
+            (usize::MAX, 8, vec![0x00; 8]),
+        ];
+        for (byte_offset, width, payload) in cases {
+            let f = field(byte_offset, width, Endianness::Big, false);
+            assert_eq!(decode_field(&f, &payload), None, "offset {}", byte_offset);
+        }
+
+        let decoded = decode(
+            &decoder(vec![field(0, 4, Endianness::Big, false)]),
+            &[0x01, 0x02],
+        );
+        assert_eq!(decoded[0].value, None);
+        assert!(!decoded[0].valid);
+    }
+
+    #[test]
+    fn flags_values_outside_the_valid_range() {
+        let f = DecoderField {
+            valid_min: Some(10.0),
+            valid_max: Some(20.0),
+            ..field(0, 1, Endianness::Big, false)
+        };
+        let decoder = decoder(vec![f]);
+        let cases = [(9, false), (10, true), (15, true), (20, true), (21, false)];
+        for (raw, valid) in cases {
+            let decoded = decode(&decoder, &[raw]);
+            assert_eq!(decoded[0].value, Some(raw as f64));
+            assert_eq!(decoded[0].valid, valid, "raw {}", raw);
+        }
+    }
+
+    #[test]
+    fn validates_decoders() {
+        let valid = field(0, 2, Endianness::Big, false);
+        assert_eq!(validate(&decoder(vec![valid.clone()])), Ok(()));
+        assert_eq!(
+            validate(&decoder(vec![DecoderField {
+                valid_min: Some(5.0),
+                valid_max: Some(5.0),
+                ..valid.clone()
+            }])),
+            Ok(())
+        );
+
+        let cases = [
+            (vec![], "at least one field"),
+            (
+                vec![DecoderField {
+                    name: "  ".into(),
+                    ..valid.clone()
+                }],
+                "cannot be empty",
+            ),
+            (vec![valid.clone(), valid.clone()], "Duplicate field name"),
+            (
+                vec![field(0, 3, Endianness::Big, false)],
+                "expected 1, 2, 4 or 8",
+            ),
+            (
+                vec![DecoderField {
+                    scale: f64::NAN,
+                    ..valid.clone()
+                }],
+                "finite scale and offset",
+            ),
+            (
+                vec![DecoderField {
+                    offset: f64::INFINITY,
+                    ..valid.clone()
+                }],
+                "finite scale and offset",
+            ),
+            (
+                vec![DecoderField {
+                    valid_min: Some(6.0),
+                    valid_max: Some(5.0),
+                    ..valid.clone()
+                }],
+                "validMin greater than validMax",
+            ),
+        ];
+        for (fields, expected) in cases {
+            let error = validate(&decoder(fields)).unwrap_err();
+            assert!(error.contains(expected), "{}", error);
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use crate::framing::errors::FramingError;
+use std::fmt;
+
+/// Length of an encoded address: six shifted callsign characters and an SSID byte
+const ADDRESS_LEN: usize = 7;
+/// Destination, source and up to eight digipeaters
+const MAX_ADDRESSES: usize = 10;
+/// Control field of an unnumbered information frame, ignoring the poll/final bit
+const UI_CONTROL: u8 = 0x03;
+const FCS_LEN: usize = 2;
+
+/// A station address, shown as `CALL-SSID` (or `CALL` when the SSID is 0)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Address {
+    pub callsign: String,
+    pub ssid: u8,
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ssid == 0 {
+            write!(f, "{}", self.callsign)
+        } else {
+            write!(f, "{}-{}", self.callsign, self.ssid)
+        }
+    }
+}
+
+/// A decoded AX.25 UI frame
+#[derive(Debug, Clone)]
+pub struct Ax25Frame {
+    pub destination: Address,
+    pub source: Address,
+    pub digipeaters: Vec<Address>,
+    pub pid: u8,
+    pub info: Vec<u8>,
+}
+
+/// Parse an AX.25 UI frame. When `has_fcs` is set, the last two bytes are checked as
+/// the frame check sequence and excluded from the frame.
+pub fn parse(frame: &[u8], has_fcs: bool) -> Result<Ax25Frame, FramingError> {
+    let frame = if has_fcs {
+        if frame.len() < FCS_LEN {
+            return Err(FramingError::Ax25("frame too short for an FCS".into()));
+        }
+        let (body, fcs_bytes) = frame.split_at(frame.len() - FCS_LEN);
+        let expected = u16::from_le_bytes([fcs_bytes[0], fcs_bytes[1]]);
+        let actual = fcs(body);
+        if expected != actual {
+            return Err(FramingError::FcsMismatch { expected, actual });
+        }
+        body
+    } else {
+        frame
+    };
+
+    let mut addresses = Vec::new();
+    let mut offset = 0;
+    loop {
+        let bytes = frame
+            .get(offset..offset + ADDRESS_LEN)
+            .ok_or_else(|| FramingError::Ax25("truncated address field".into()))?;
+        addresses.push(parse_address(bytes)?);
+        offset += ADDRESS_LEN;
+
+        // The extension bit marks the last address
+        if bytes[ADDRESS_LEN - 1] & 0x01 == 1 {
+            break;
+        }
+        if addresses.len() == MAX_ADDRESSES {
+            return Err(FramingError::Ax25("too many addresses".into()));
+        }
+    }
+    if addresses.len() < 2 {
+        return Err(FramingError::Ax25("missing source address".into()));
+    }
+
+    let (&control, rest) = frame[offset..]
+        .split_first()
+        .ok_or_else(|| FramingError::Ax25("missing control field".into()))?;
+    if control & !0x10 != UI_CONTROL {
+        return Err(FramingError::Ax25(format!(
+            "control field {:#04x} is not a UI frame",
+            control
+        )));
+    }
+    let (&pid, info) = rest
+        .split_first()
+        .ok_or_else(|| FramingError::Ax25("missing PID".into()))?;
+
+    let mut addresses = addresses.into_iter();
+    let destination = addresses.next().unwrap();
+    let source = addresses.next().unwrap();
+
+    Ok(Ax25Frame {
+        destination,
+        source,
+        digipeaters: addresses.collect(),
+        pid,
+        info: info.to_vec(),
+    })
+}
+
+fn parse_address(bytes: &[u8]) -> Result<Address, FramingError> {
+    let callsign: String = bytes[..ADDRESS_LEN - 1]
+        .iter()
+        .map(|b| (b >> 1) as char)
+        .collect::<String>()
+        .trim_end()
+        .to_string();
+    if callsign.is_empty() || !callsign.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(FramingError::Ax25(format!(
+            "invalid callsign '{}'",
+            callsign
+        )));
+    }
+
+    Ok(Address {
+        callsign,
+        ssid: (bytes[ADDRESS_LEN - 1] >> 1) & 0x0F,
+    })
+}
+
+/// CRC-16/X.25 frame check sequence
+pub fn fcs(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    for &byte in data {
+        crc ^= byte as u16;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0x8408
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// UI frame from N0CALL-7 to CQ with PID 0xF0 and "hello" as info field
+    const FRAME: [u8; 21] = [
+        0x86, 0xA2, 0x40, 0x40, 0x40, 0x40, 0x60, // CQ
+        0x9C, 0x60, 0x86, 0x82, 0x98, 0x98, 0x6F, // N0CALL-7, last address
+        0x03, 0xF0, b'h', b'e', b'l', b'l', b'o',
+    ];
+    const FRAME_FCS: u16 = 0x0FDB;
+
+    fn with_fcs(frame: &[u8], fcs: u16) -> Vec<u8> {
+        let mut bytes = frame.to_vec();
+        bytes.extend_from_slice(&fcs.to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn fcs_matches_the_crc_16_x25_check_value() {
+        assert_eq!(fcs(b"123456789"), 0x906E);
+        assert_eq!(fcs(&FRAME), FRAME_FCS);
+    }
+
+    #[test]
+    fn parses_a_ui_frame() {
+        let frame = parse(&FRAME, false).unwrap();
+
+        assert_eq!(frame.destination.to_string(), "CQ");
+        assert_eq!(
+            frame.source,
+            Address {
+                callsign: "N0CALL".into(),
+                ssid: 7
+            }
+        );
+        assert_eq!(frame.source.to_string(), "N0CALL-7");
+        assert!(frame.digipeaters.is_empty());
+        assert_eq!(frame.pid, 0xF0);
+        assert_eq!(frame.info, b"hello");
+    }
+
+    #[test]
+    fn checks_and_strips_the_fcs() {
+        let frame = parse(&with_fcs(&FRAME, FRAME_FCS), true).unwrap();
+        assert_eq!(frame.info, b"hello");
+
+        let mut corrupted = with_fcs(&FRAME, FRAME_FCS);
+        corrupted[16] ^= 0x01;
+        assert!(matches!(
+            parse(&corrupted, true),
+            Err(FramingError::FcsMismatch {
+                expected: FRAME_FCS,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn decodes_digipeater_addresses() {
+        let mut bytes = FRAME[..14].to_vec();
+        // Clear the source's extension bit and append WIDE2-1 as the last address
+        bytes[13] &= !0x01;
+        bytes.extend(b"WIDE2 ".iter().map(|c| c << 1));
+        bytes.push(0x60 | (1 << 1) | 0x01);
+        bytes.extend_from_slice(&FRAME[14..]);
+
+        let frame = parse(&bytes, false).unwrap();
+        assert_eq!(frame.source.to_string(), "N0CALL-7");
+        assert_eq!(frame.digipeaters.len(), 1);
+        assert_eq!(frame.digipeaters[0].to_string(), "WIDE2-1");
+        assert_eq!(frame.info, b"hello");
+    }
+
+    #[test]
+    fn rejects_invalid_frames() {
+        // Truncated in the source address
+        assert!(parse(&FRAME[..10], false).is_err());
+        // No control field
+        assert!(parse(&FRAME[..14], false).is_err());
+        // An I frame rather than a UI frame
+        let mut bytes = FRAME.to_vec();
+        bytes[14] = 0x00;
+        assert!(parse(&bytes, false).is_err());
+        // Callsign characters that are not alphanumeric
+        let mut bytes = FRAME.to_vec();
+        bytes[7] = b'*' << 1;
+        assert!(parse(&bytes, false).is_err());
+        // Too short to hold an FCS
+        assert!(parse(&[0x01], true).is_err());
+    }
+}
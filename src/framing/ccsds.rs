@@ -0,0 +1,390 @@
+use crate::framing::errors::FramingError;
+use log::warn;
+
+const FRAME_HEADER_LEN: usize = 6;
+const OCF_LEN: usize = 4;
+const FECF_LEN: usize = 2;
+const PACKET_HEADER_LEN: usize = 6;
+
+/// First header pointer of a frame in which no packet starts
+const NO_PACKET_START: u16 = 0x7FF;
+/// First header pointer of a frame carrying only idle data
+const IDLE_DATA: u16 = 0x7FE;
+/// APID of idle packets, which only fill frames
+const IDLE_APID: u16 = 0x7FF;
+
+/// A decoded CCSDS TM transfer frame (CCSDS 132.0-B)
+#[derive(Debug, Clone)]
+pub struct TmFrame {
+    pub spacecraft_id: u16,
+    pub virtual_channel: u8,
+    pub master_channel_count: u8,
+    pub virtual_channel_count: u8,
+    /// Offset of the first packet header in `data`
+    pub first_header_pointer: u16,
+    /// Operational control field, usually a CLCW
+    pub ocf: Option<u32>,
+    pub data: Vec<u8>,
+}
+
+/// A CCSDS space packet (CCSDS 133.0-B), header included
+#[derive(Debug, Clone)]
+pub struct SpacePacket {
+    pub apid: u16,
+    pub sequence_count: u16,
+    pub bytes: Vec<u8>,
+}
+
+impl SpacePacket {
+    /// The packet data field, secondary header included
+    pub fn data(&self) -> &[u8] {
+        &self.bytes[PACKET_HEADER_LEN..]
+    }
+}
+
+/// Parse a TM transfer frame. The presence of the OCF is read from the frame header;
+/// the FECF is mission configuration, so `has_fecf` tells whether the last two bytes
+/// are checked as the frame error control field and excluded from the frame.
+pub fn parse(frame: &[u8], has_fecf: bool) -> Result<TmFrame, FramingError> {
+    let frame = if has_fecf {
+        if frame.len() < FECF_LEN {
+            return Err(FramingError::Ccsds("frame too short for a FECF".into()));
+        }
+        let (body, fecf_bytes) = frame.split_at(frame.len() - FECF_LEN);
+        let expected = u16::from_be_bytes([fecf_bytes[0], fecf_bytes[1]]);
+        let actual = fecf(body);
+        if expected != actual {
+            return Err(FramingError::FecfMismatch { expected, actual });
+        }
+        body
+    } else {
+        frame
+    };
+
+    if frame.len() < FRAME_HEADER_LEN {
+        return Err(FramingError::Ccsds("truncated primary header".into()));
+    }
+    let id = u16::from_be_bytes([frame[0], frame[1]]);
+    if id >> 14 != 0 {
+        return Err(FramingError::Ccsds(format!(
+            "unsupported transfer frame version {}",
+            id >> 14
+        )));
+    }
+    let has_ocf = id & 0x01 == 1;
+    let status = u16::from_be_bytes([frame[4], frame[5]]);
+    let has_secondary_header = status & 0x8000 != 0;
+    if status & 0x4000 != 0 {
+        return Err(FramingError::Ccsds(
+            "frame data is not synchronized to space packets".into(),
+        ));
+    }
+
+    let mut offset = FRAME_HEADER_LEN;
+    if has_secondary_header {
+        // The secondary header identification holds its length minus one
+        let length = *frame
+            .get(offset)
+            .ok_or_else(|| FramingError::Ccsds("truncated secondary header".into()))?
+            & 0x3F;
+        offset += length as usize + 1;
+    }
+
+    let end = if has_ocf {
+        frame.len().checked_sub(OCF_LEN)
+    } else {
+        Some(frame.len())
+    }
+    .filter(|end| *end >= offset)
+    .ok_or_else(|| FramingError::Ccsds("frame too short for its header fields".into()))?;
+    let data = frame[offset..end].to_vec();
+    let ocf = has_ocf
+        .then(|| u32::from_be_bytes([frame[end], frame[end + 1], frame[end + 2], frame[end + 3]]));
+
+    let first_header_pointer = status & 0x07FF;
+    if first_header_pointer != NO_PACKET_START
+        && first_header_pointer != IDLE_DATA
+        && first_header_pointer as usize >= data.len()
+    {
+        return Err(FramingError::Ccsds(format!(
+            "first header pointer {} is outside the data field",
+            first_header_pointer
+        )));
+    }
+
+    Ok(TmFrame {
+        spacecraft_id: (id >> 4) & 0x03FF,
+        virtual_channel: ((id >> 1) & 0x07) as u8,
+        master_channel_count: frame[2],
+        virtual_channel_count: frame[3],
+        first_header_pointer,
+        ocf,
+        data,
+    })
+}
+
+/// Reassembles the space packets of one virtual channel. Packets may span frames, so
+/// the tail of a frame is kept until the next frame of the channel arrives. A gap in
+/// the virtual channel frame count drops the incomplete packet.
+#[derive(Debug, Default)]
+pub struct VirtualChannel {
+    last_count: Option<u8>,
+    partial: Vec<u8>,
+}
+
+impl VirtualChannel {
+    /// Extract the packets completed by a frame of this channel. Idle packets are
+    /// left out.
+    pub fn push(&mut self, frame: &TmFrame) -> Vec<SpacePacket> {
+        let count = frame.virtual_channel_count;
+        if self.last_count == Some(count) {
+            // The previous frame repeated: its complete packets can be extracted again,
+            // as they are stored once, but the packet in progress must not be disturbed
+            return match frame.first_header_pointer {
+                NO_PACKET_START | IDLE_DATA => Vec::new(),
+                pointer => {
+                    let mut buffer = frame.data[pointer as usize..].to_vec();
+                    take_packets(&mut buffer)
+                }
+            };
+        }
+
+        let continues = self.last_count.map(|c| c.wrapping_add(1)) == Some(count);
+        if !continues && !self.partial.is_empty() {
+            warn!(
+                "Virtual channel {} skipped from frame {} to {}, dropping an incomplete packet",
+                frame.virtual_channel,
+                self.last_count.unwrap_or_default(),
+                count
+            );
+            self.partial.clear();
+        }
+        self.last_count = Some(count);
+
+        let mut packets = Vec::new();
+        match frame.first_header_pointer {
+            IDLE_DATA => self.partial.clear(),
+            NO_PACKET_START => {
+                if continues {
+                    self.partial.extend_from_slice(&frame.data);
+                    packets = take_packets(&mut self.partial);
+                }
+            }
+            pointer => {
+                let (head, tail) = frame.data.split_at(pointer as usize);
+                if continues && !self.partial.is_empty() {
+                    self.partial.extend_from_slice(head);
+                    packets = take_packets(&mut self.partial);
+                    if !self.partial.is_empty() {
+                        warn!(
+                            "Virtual channel {} packet does not end at the first header pointer",
+                            frame.virtual_channel
+                        );
+                    }
+                }
+                self.partial = tail.to_vec();
+                packets.extend(take_packets(&mut self.partial));
+            }
+        }
+
+        packets
+    }
+}
+
+/// Remove the complete packets at the start of a buffer, leaving an incomplete one
+fn take_packets(buffer: &mut Vec<u8>) -> Vec<SpacePacket> {
+    let mut packets = Vec::new();
+    let mut offset = 0;
+
+    while let Some(header) = buffer.get(offset..offset + PACKET_HEADER_LEN) {
+        if header[0] >> 5 != 0 {
+            // Not a packet header: the channel lost synchronization
+            warn!(
+                "Dropping {} bytes of unsynchronized packet data",
+                buffer.len() - offset
+            );
+            buffer.clear();
+            return packets;
+        }
+        let length = PACKET_HEADER_LEN + u16::from_be_bytes([header[4], header[5]]) as usize + 1;
+        let Some(bytes) = buffer.get(offset..offset + length) else {
+            break;
+        };
+
+        let id = u16::from_be_bytes([header[0], header[1]]);
+        let sequence = u16::from_be_bytes([header[2], header[3]]);
+        let apid = id & 0x07FF;
+        if apid != IDLE_APID {
+            packets.push(SpacePacket {
+                apid,
+                sequence_count: sequence & 0x3FFF,
+                bytes: bytes.to_vec(),
+            });
+        }
+        offset += length;
+    }
+
+    buffer.drain(..offset);
+    packets
+}
+
+/// CRC-16/CCITT-FALSE frame error control field
+pub fn fecf(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPACECRAFT_ID: u16 = 0x1AB;
+
+    /// Transfer frame of virtual channel 2 without secondary header
+    fn frame(vc_count: u8, first_header_pointer: u16, data: &[u8], ocf: Option<u32>) -> Vec<u8> {
+        let id = SPACECRAFT_ID << 4 | 2 << 1 | ocf.is_some() as u16;
+        // Segment length ID 0b11, as for unsegmented packets
+        let status = 0x1800 | first_header_pointer;
+
+        let mut bytes = id.to_be_bytes().to_vec();
+        bytes.extend([0x10, vc_count]);
+        bytes.extend(status.to_be_bytes());
+        bytes.extend_from_slice(data);
+        if let Some(ocf) = ocf {
+            bytes.extend(ocf.to_be_bytes());
+        }
+        bytes
+    }
+
+    fn packet(apid: u16, sequence_count: u16, data: &[u8]) -> Vec<u8> {
+        let mut bytes = (0x0800 | apid).to_be_bytes().to_vec();
+        bytes.extend((0xC000 | sequence_count).to_be_bytes());
+        bytes.extend((data.len() as u16 - 1).to_be_bytes());
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    fn parse_pushed(channel: &mut VirtualChannel, frame: &[u8]) -> Vec<(u16, u16, Vec<u8>)> {
+        channel
+            .push(&parse(frame, false).unwrap())
+            .into_iter()
+            .map(|p| (p.apid, p.sequence_count, p.data().to_vec()))
+            .collect()
+    }
+
+    #[test]
+    fn fecf_matches_the_crc_16_ccitt_false_check_value() {
+        assert_eq!(fecf(b"123456789"), 0x29B1);
+    }
+
+    #[test]
+    fn parses_the_primary_header_and_ocf() {
+        let data = packet(0x42, 7, &[1, 2, 3]);
+        let frame = parse(&frame(9, 0, &data, Some(0x0102_0304)), false).unwrap();
+
+        assert_eq!(frame.spacecraft_id, SPACECRAFT_ID);
+        assert_eq!(frame.virtual_channel, 2);
+        assert_eq!(frame.master_channel_count, 0x10);
+        assert_eq!(frame.virtual_channel_count, 9);
+        assert_eq!(frame.first_header_pointer, 0);
+        assert_eq!(frame.ocf, Some(0x0102_0304));
+        assert_eq!(frame.data, data);
+    }
+
+    #[test]
+    fn checks_and_strips_the_fecf() {
+        let mut bytes = frame(0, 0, &packet(0x42, 0, &[1]), None);
+        let crc = fecf(&bytes);
+        bytes.extend(crc.to_be_bytes());
+        assert_eq!(parse(&bytes, true).unwrap().data, packet(0x42, 0, &[1]));
+
+        bytes[7] ^= 0xFF;
+        assert!(matches!(
+            parse(&bytes, true),
+            Err(FramingError::FecfMismatch { expected, .. }) if expected == crc
+        ));
+    }
+
+    #[test]
+    fn rejects_a_first_header_pointer_outside_the_data() {
+        let data = packet(0x42, 0, &[1, 2]);
+        assert!(parse(&frame(0, data.len() as u16, &data, None), false).is_err());
+        assert!(parse(&frame(0, NO_PACKET_START, &data, None), false).is_ok());
+    }
+
+    #[test]
+    fn extracts_the_packets_of_a_frame() {
+        let mut data = packet(0x42, 1, &[1, 2]);
+        data.extend(packet(IDLE_APID, 0, &[0; 3]));
+        data.extend(packet(0x43, 2, &[3]));
+
+        let mut channel = VirtualChannel::default();
+        assert_eq!(
+            parse_pushed(&mut channel, &frame(0, 0, &data, None)),
+            [(0x42, 1, vec![1, 2]), (0x43, 2, vec![3])]
+        );
+    }
+
+    #[test]
+    fn reassembles_packets_spanning_frames() {
+        let first = packet(0x42, 1, &[1, 2, 3, 4, 5, 6, 7, 8]);
+        let second = packet(0x43, 2, &[9]);
+        // The first frame holds the start of the first packet, the second frame its
+        // middle and the third its end, followed by the second packet
+        let (head, rest) = first.split_at(8);
+        let (middle, tail) = rest.split_at(4);
+        let mut last = tail.to_vec();
+        last.extend(&second);
+
+        let mut channel = VirtualChannel::default();
+        assert!(parse_pushed(&mut channel, &frame(0, 0, head, None)).is_empty());
+        assert!(parse_pushed(&mut channel, &frame(1, NO_PACKET_START, middle, None)).is_empty());
+        assert_eq!(
+            parse_pushed(&mut channel, &frame(2, tail.len() as u16, &last, None)),
+            [(0x42, 1, vec![1, 2, 3, 4, 5, 6, 7, 8]), (0x43, 2, vec![9])]
+        );
+    }
+
+    #[test]
+    fn drops_the_packet_in_progress_on_a_frame_count_gap() {
+        let first = packet(0x42, 1, &[1, 2, 3, 4]);
+        let second = packet(0x43, 2, &[5]);
+        let (head, tail) = first.split_at(6);
+        let mut last = tail.to_vec();
+        last.extend(&second);
+
+        let mut channel = VirtualChannel::default();
+        assert!(parse_pushed(&mut channel, &frame(0, 0, head, None)).is_empty());
+        assert_eq!(
+            parse_pushed(&mut channel, &frame(2, tail.len() as u16, &last, None)),
+            [(0x43, 2, vec![5])]
+        );
+    }
+
+    #[test]
+    fn a_repeated_frame_leaves_the_packet_in_progress_alone() {
+        let first = packet(0x42, 1, &[1, 2, 3, 4]);
+        let (head, tail) = first.split_at(6);
+        let mut data = packet(0x41, 0, &[0]);
+        data.extend(head);
+
+        let mut channel = VirtualChannel::default();
+        let repeated = frame(0, 0, &data, None);
+        assert_eq!(parse_pushed(&mut channel, &repeated), [(0x41, 0, vec![0])]);
+        assert_eq!(parse_pushed(&mut channel, &repeated), [(0x41, 0, vec![0])]);
+        assert_eq!(
+            parse_pushed(&mut channel, &frame(1, NO_PACKET_START, tail, None)),
+            [(0x42, 1, vec![1, 2, 3, 4])]
+        );
+    }
+}
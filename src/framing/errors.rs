@@ -0,0 +1,42 @@
+use std::fmt;
+
+/// Error type for link-layer frame parsing
+#[derive(Debug)]
+pub enum FramingError {
+    /// The KISS stream held no data frame or a broken escape sequence
+    Kiss(String),
+
+    /// The AX.25 frame is truncated or is not a UI frame
+    Ax25(String),
+
+    /// The frame check sequence does not match the frame contents
+    FcsMismatch { expected: u16, actual: u16 },
+
+    /// The CCSDS transfer frame or one of its space packets is malformed
+    Ccsds(String),
+
+    /// The frame error control field does not match the transfer frame contents
+    FecfMismatch { expected: u16, actual: u16 },
+}
+
+impl fmt::Display for FramingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FramingError::Kiss(msg) => write!(f, "Invalid KISS frame: {}", msg),
+            FramingError::Ax25(msg) => write!(f, "Invalid AX.25 frame: {}", msg),
+            FramingError::FcsMismatch { expected, actual } => write!(
+                f,
+                "AX.25 FCS mismatch: expected {:#06x}, got {:#06x}",
+                expected, actual
+            ),
+            FramingError::Ccsds(msg) => write!(f, "Invalid CCSDS frame: {}", msg),
+            FramingError::FecfMismatch { expected, actual } => write!(
+                f,
+                "CCSDS FECF mismatch: expected {:#06x}, got {:#06x}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FramingError {}
@@ -0,0 +1,94 @@
+use crate::framing::errors::FramingError;
+
+const FEND: u8 = 0xC0;
+const FESC: u8 = 0xDB;
+const TFEND: u8 = 0xDC;
+const TFESC: u8 = 0xDD;
+
+/// Command nibble of a KISS data frame
+const DATA_FRAME: u8 = 0x00;
+
+/// Extract the first data frame of a KISS stream, without its command byte and
+/// with escape sequences resolved. Non-data frames (TNC parameters) are skipped.
+pub fn unwrap(stream: &[u8]) -> Result<Vec<u8>, FramingError> {
+    for frame in stream.split(|b| *b == FEND).filter(|f| !f.is_empty()) {
+        let frame = unescape(frame)?;
+        let Some((command, data)) = frame.split_first() else {
+            continue;
+        };
+        // The high nibble is the TNC port
+        if command & 0x0F == DATA_FRAME && !data.is_empty() {
+            return Ok(data.to_vec());
+        }
+    }
+
+    Err(FramingError::Kiss("no data frame found".into()))
+}
+
+fn unescape(frame: &[u8]) -> Result<Vec<u8>, FramingError> {
+    let mut data = Vec::with_capacity(frame.len());
+    let mut bytes = frame.iter();
+
+    while let Some(&b) = bytes.next() {
+        if b != FESC {
+            data.push(b);
+            continue;
+        }
+        match bytes.next() {
+            Some(&TFEND) => data.push(FEND),
+            Some(&TFESC) => data.push(FESC),
+            Some(other) => {
+                return Err(FramingError::Kiss(format!(
+                    "invalid escape sequence {:#04x}",
+                    other
+                )))
+            }
+            None => return Err(FramingError::Kiss("truncated escape sequence".into())),
+        }
+    }
+
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unwraps_a_data_frame() {
+        let stream = [FEND, 0x00, 0x01, 0x02, 0x03, FEND];
+        assert_eq!(unwrap(&stream).unwrap(), [0x01, 0x02, 0x03]);
+    }
+
+    #[test]
+    fn resolves_escape_sequences() {
+        let stream = [FEND, 0x00, FESC, TFEND, 0x01, FESC, TFESC, TFEND, FEND];
+        assert_eq!(unwrap(&stream).unwrap(), [FEND, 0x01, FESC, TFEND]);
+    }
+
+    #[test]
+    fn skips_non_data_frames() {
+        // A TXDELAY parameter frame, then a data frame on TNC port 1
+        let stream = [FEND, 0x01, 0x32, FEND, FEND, 0x10, 0xAA, FEND];
+        assert_eq!(unwrap(&stream).unwrap(), [0xAA]);
+    }
+
+    #[test]
+    fn rejects_invalid_escapes() {
+        assert!(matches!(
+            unwrap(&[FEND, 0x00, 0x01, FESC, FEND]),
+            Err(FramingError::Kiss(message)) if message.contains("truncated")
+        ));
+        assert!(matches!(
+            unwrap(&[FEND, 0x00, FESC, 0x01, FEND]),
+            Err(FramingError::Kiss(message)) if message.contains("invalid")
+        ));
+    }
+
+    #[test]
+    fn rejects_streams_without_data() {
+        assert!(unwrap(&[]).is_err());
+        assert!(unwrap(&[FEND, FEND]).is_err());
+        assert!(unwrap(&[FEND, 0x00, FEND]).is_err());
+    }
+}
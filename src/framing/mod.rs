@@ -0,0 +1,4 @@
+pub mod ax25;
+pub mod ccsds;
+pub mod errors;
+pub mod kiss;
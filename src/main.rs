@@ -4,45 +4,76 @@ use utoipa_swagger_ui::SwaggerUi;
 
 mod config;
 mod database;
+mod decoding;
+mod framing;
 mod messaging;
 mod models;
 mod repository;
 mod routes;
 mod services;
+mod tracking;
 
-use config::{Config, DatabaseConfig, MessageBrokerConfig, ServerConfig};
+use config::{
+    BrokerTlsConfig, Config, DatabaseConfig, GroundStationsConfig, IngestConfig, JobsConfig,
+    MessageBrokerConfig, ServerConfig, TelemetryConfig, TopicsConfig,
+};
 use database::create_pool;
-use messaging::{broker::MqttBroker, receiver::MqttReceiver};
+use messaging::{
+    broker::MqttBroker, connection::ConnectionState, handler::MessageHandler, ingest::ingest_queue,
+    receiver::MqttReceiver, topics::TopicScheme,
+};
 use models::{
-    commands::TestMessage,
+    commands::{JobMessage, JobSatellite, TestMessage},
+    entities::{
+        AlarmSeverity, CcsdsConfig, DecoderField, Endianness, Job, JobStatus, TelemetryDecoder,
+        TelemetryLimit,
+    },
     requests::{
-        GroundStationCreateRequest, HistoricTelemetryRequest, LatestTelemetryRequest,
-        SatelliteCreateRequest, TleUpdateRequest,
+        AlarmAcknowledgeRequest, AlarmListRequest, ControlCommandRequest, DeadLetterListRequest,
+        DeadLetterReplayRequest, ExportFormat, GroundStationCreateRequest,
+        HistoricTelemetryRequest, JobListRequest, LatestTelemetryRequest, LiveTelemetryRequest,
+        PassPredictionRequest, RawEncoding, SatelliteCreateRequest, SortOrder,
+        TelemetryAggregateRequest, TelemetryExportRequest, TelemetryPageRequest, TleUpdateRequest,
     },
     responses::*,
 };
 use repository::{
-    ground_station::GroundStationRepository, job::JobRepository, satellite::SatelliteRepository,
-    telemetry::TelemetryRepository,
+    alarm::AlarmRepository, dead_letter::DeadLetterRepository,
+    ground_station::GroundStationRepository, job::JobRepository,
+    job_status_update::JobStatusUpdateRepository, satellite::SatelliteRepository,
+    telemetry::TelemetryRepository, telemetry_decoder::TelemetryDecoderRepository,
+    telemetry_limit::TelemetryLimitRepository,
 };
 use routes::{
+    alarms::{
+        acknowledge_alarm, fetch_alarm, fetch_alarms, get_telemetry_limits, set_telemetry_limits,
+    },
     config::get_config,
-    control::send_command,
+    control::{get_broker_status, get_ingest_metrics, send_command},
+    dead_letters::{
+        fetch_dead_letter, fetch_dead_letters, replay_dead_letter, replay_dead_letters,
+    },
     ground_stations::{
         create_ground_station, fetch_all_ground_stations, fetch_ground_station,
-        set_tle_for_ground_station,
+        fetch_ground_station_status, set_tle_for_ground_station,
     },
-    jobs::create_job,
+    jobs::{create_job, fetch_all_jobs, fetch_job, fetch_job_status},
     satellites::{
         create_satellite, delete_satellite, fetch_all_satellites, fetch_satellite,
-        update_satellite_tle,
+        get_ccsds_config, set_ccsds_config, update_satellite_tle,
+    },
+    telemetry::{
+        export_telemetry, get_aggregated_telemetry, get_apid_decoder, get_historic_telemetry,
+        get_latest_telemetry, get_telemetry_decoder, get_telemetry_page, get_telemetry_receptions,
+        set_apid_decoder, set_telemetry_decoder, stream_live_telemetry,
     },
-    telemetry::{get_historic_telemetry, get_latest_telemetry},
+    tracking::{get_ground_station_passes, get_satellite_passes},
 };
 use services::{
+    alarm_service::AlarmService, dead_letter_service::DeadLetterService,
     ground_station_service::GroundStationService, job_service::JobService,
     message_service::MessageService, satellite_service::SatelliteService,
-    telemetry_service::TelemetryService,
+    telemetry_service::TelemetryService, tracking_service::TrackingService,
 };
 use std::sync::Arc;
 use tokio::signal;
@@ -55,41 +86,125 @@ use tokio::sync::oneshot;
         routes::ground_stations::create_ground_station,
         routes::ground_stations::fetch_all_ground_stations,
         routes::ground_stations::fetch_ground_station,
+        routes::ground_stations::fetch_ground_station_status,
         routes::ground_stations::set_tle_for_ground_station,
         // Telemetry
         routes::telemetry::get_latest_telemetry,
         routes::telemetry::get_historic_telemetry,
+        routes::telemetry::get_aggregated_telemetry,
+        routes::telemetry::export_telemetry,
+        routes::telemetry::stream_live_telemetry,
+        routes::telemetry::get_telemetry_receptions,
+        routes::telemetry::get_telemetry_page,
+        routes::telemetry::get_telemetry_decoder,
+        routes::telemetry::set_telemetry_decoder,
+        routes::telemetry::get_apid_decoder,
+        routes::telemetry::set_apid_decoder,
         // Config & Control
         routes::config::get_config,
         routes::control::send_command,
+        routes::control::get_broker_status,
+        routes::control::get_ingest_metrics,
         // Jobs
         routes::jobs::create_job,
+        routes::jobs::fetch_all_jobs,
+        routes::jobs::fetch_job,
+        routes::jobs::fetch_job_status,
         // Satellites
         routes::satellites::fetch_all_satellites,
         routes::satellites::fetch_satellite,
         routes::satellites::create_satellite,
         routes::satellites::update_satellite_tle,
+        routes::satellites::get_ccsds_config,
+        routes::satellites::set_ccsds_config,
         routes::satellites::delete_satellite,
+        // Tracking
+        routes::tracking::get_satellite_passes,
+        routes::tracking::get_ground_station_passes,
+        // Alarms
+        routes::alarms::fetch_alarms,
+        routes::alarms::fetch_alarm,
+        routes::alarms::acknowledge_alarm,
+        routes::alarms::get_telemetry_limits,
+        routes::alarms::set_telemetry_limits,
+        // Dead letters
+        routes::dead_letters::fetch_dead_letters,
+        routes::dead_letters::fetch_dead_letter,
+        routes::dead_letters::replay_dead_letter,
+        routes::dead_letters::replay_dead_letters,
     ),
     components(schemas(
+        Job,
+        JobListRequest,
+        JobPageResponse,
+        JobStatus,
+        JobStatusResponse,
+        JobStatusUpdateResponse,
         TelemetryResponse,
+        TelemetryParameter,
+        TelemetryReceptionResponse,
+        TelemetryPageResponse,
+        TelemetryPageRequest,
+        TelemetryAggregateResponse,
+        TelemetryAggregateRequest,
+        TelemetryExportRequest,
+        ExportFormat,
+        LiveTelemetryRequest,
+        ControlCommandRequest,
+        TelemetryBucket,
+        ParameterAggregate,
+        RawEncoding,
+        SortOrder,
+        TelemetryDecoder,
+        DecoderField,
+        Endianness,
+        CcsdsConfig,
         ConfigResponse,
+        BrokerStatusResponse,
+        IngestMetricsResponse,
+        ConnectionState,
         HistoricTelemetryRequest,
         LatestTelemetryRequest,
         ServerConfig,
         DatabaseConfig,
         MessageBrokerConfig,
+        BrokerTlsConfig,
+        JobsConfig,
+        TelemetryConfig,
+        IngestConfig,
+        GroundStationsConfig,
+        TopicsConfig,
         TestMessage,
+        JobMessage,
+        JobSatellite,
         GroundStationCreateRequest,
+        GroundStationResponse,
+        GroundStationStatusResponse,
+        StationStatus,
         SatelliteCreateRequest,
-        TleUpdateRequest
+        TleUpdateRequest,
+        PassPredictionRequest,
+        PassResponse,
+        TelemetryLimit,
+        AlarmSeverity,
+        AlarmResponse,
+        AlarmListRequest,
+        AlarmAcknowledgeRequest,
+        DeadLetterResponse,
+        PayloadEncoding,
+        DeadLetterListRequest,
+        DeadLetterReplayRequest,
+        DeadLetterReplayResponse
     )),
     tags(
         (name = "Telemetry", description = "Telemetry endpoints"),
         (name = "Config", description = "Configuration endpoints"),
         (name = "Ground Stations", description = "Ground station management"),
         (name = "Jobs", description = "Job management"),
-        (name = "Satellites", description = "Satellite management endpoints")
+        (name = "Satellites", description = "Satellite management endpoints"),
+        (name = "Tracking", description = "Orbit propagation and pass prediction"),
+        (name = "Alarms", description = "Telemetry limits and alarms"),
+        (name = "Dead Letters", description = "Inbound messages that could not be processed")
     ),
     info(
         title = "Rust API with Utoipa",
@@ -119,46 +234,118 @@ async fn main() -> std::io::Result<()> {
 
     // Initialize repositories & services
     let telemetry_repository = TelemetryRepository::new(pool.clone());
-    let telemetry_service = Arc::new(TelemetryService::new(telemetry_repository));
+    let telemetry_service = Arc::new(TelemetryService::new(
+        telemetry_repository,
+        SatelliteRepository::new(pool.clone()),
+        TelemetryDecoderRepository::new(pool.clone()),
+        shared_config.telemetry.clone(),
+    ));
 
     let ground_station_repository = GroundStationRepository::new(pool.clone());
-    let ground_station_service = Arc::new(GroundStationService::new(ground_station_repository));
-
-    let job_repository = JobRepository::new(pool.clone());
-    let job_service = Arc::new(JobService::new(job_repository));
+    let ground_station_service = Arc::new(GroundStationService::new(
+        ground_station_repository,
+        shared_config.ground_stations.clone(),
+    ));
 
     let satellite_repository = SatelliteRepository::new(pool.clone());
     let satellite_service = Arc::new(SatelliteService::new(satellite_repository));
 
+    let tracking_service = Arc::new(TrackingService::new(
+        SatelliteRepository::new(pool.clone()),
+        GroundStationRepository::new(pool.clone()),
+    ));
+
+    let alarm_service = Arc::new(AlarmService::new(
+        AlarmRepository::new(pool.clone()),
+        TelemetryLimitRepository::new(pool.clone()),
+        SatelliteRepository::new(pool.clone()),
+    ));
+
     // Setup MQTT broker & receiver
-    let keepalive = std::time::Duration::from_secs(shared_config.message_broker.keep_alive as u64);
-    let (broker, eventloop) = MqttBroker::new(
-        &shared_config.message_broker.host,
-        shared_config.message_broker.port,
-        keepalive,
+    let topics = Arc::new(
+        TopicScheme::new(&shared_config.message_broker.topics)
+            .expect("Invalid MQTT topic configuration"),
     );
+    let (broker, eventloop) =
+        MqttBroker::new(&shared_config.message_broker).expect("Invalid MQTT broker configuration");
     let client = broker.client();
-    let messaging_service = Arc::new(MessageService::new(broker));
+    let connection = broker.connection();
+    let messaging_service = Arc::new(MessageService::new(broker, topics.clone()));
+
+    let job_repository = JobRepository::new(pool.clone());
+    let job_service = Arc::new(JobService::new(
+        job_repository,
+        JobStatusUpdateRepository::new(pool.clone()),
+        SatelliteRepository::new(pool.clone()),
+        GroundStationRepository::new(pool.clone()),
+        messaging_service.clone(),
+        shared_config.jobs.clone(),
+    ));
 
     // Start MQTT event loop in background
-    let mut recv = MqttReceiver::from_client(client, eventloop, telemetry_service.clone());
+    let handler = Arc::new(MessageHandler::new(
+        telemetry_service.clone(),
+        job_service.clone(),
+        alarm_service.clone(),
+        ground_station_service.clone(),
+        topics.clone(),
+    ));
+    let dead_letter_service = Arc::new(DeadLetterService::new(
+        DeadLetterRepository::new(pool.clone()),
+        handler.clone(),
+    ));
+    let (ingest_queue, ingest_writer) =
+        ingest_queue(&shared_config.ingest, handler, dead_letter_service.clone());
+    let ingest_stats = ingest_queue.stats();
+    let mut recv =
+        MqttReceiver::from_client(client, eventloop, topics.clone(), ingest_queue, connection);
+    // Publishes the offline presence on shutdown
+    let presence_service = messaging_service.clone();
 
     println!("============= API SERVER STARTING =============");
     println!("Available endpoints:");
     println!("  - GET    /api/telemetry/latest");
     println!("  - GET    /api/telemetry/history");
+    println!("  - GET    /api/telemetry/aggregate");
+    println!("  - GET    /api/telemetry/export");
+    println!("  - GET    /api/telemetry/live");
+    println!("  - GET    /api/telemetry/frames/{{id}}/receptions");
+    println!("  - GET    /api/satellites/{{satellite}}/telemetry");
+    println!("  - GET    /api/satellites/{{id}}/telemetry/decoder");
+    println!("  - PUT    /api/satellites/{{id}}/telemetry/decoder");
+    println!("  - GET    /api/satellites/{{id}}/telemetry/decoders/{{apid}}");
+    println!("  - PUT    /api/satellites/{{id}}/telemetry/decoders/{{apid}}");
     println!("  - GET    /api/config");
-    println!("  - POST   /api/control");
+    println!("  - POST   /api/control/command?gsId=<id>");
+    println!("  - GET    /api/control/broker");
+    println!("  - GET    /api/control/ingest");
     println!("  - POST   /api/jobs");
+    println!("  - GET    /api/jobs");
+    println!("  - GET    /api/jobs/{{id}}");
+    println!("  - GET    /api/jobs/{{id}}/status");
     println!("  - GET    /api/ground-stations");
     println!("  - GET    /api/ground-stations/{{id}}");
+    println!("  - GET    /api/ground-stations/{{id}}/status");
     println!("  - POST   /api/ground-stations");
     println!("  - PUT    /api/ground-stations/{{id}}/tle");
+    println!("  - GET    /api/ground-stations/{{id}}/passes");
     println!("  - GET    /api/satellites");
     println!("  - GET    /api/satellites/{{id}}");
     println!("  - POST   /api/satellites");
     println!("  - PUT    /api/satellites/{{id}}/tle");
+    println!("  - GET    /api/satellites/{{id}}/ccsds");
+    println!("  - PUT    /api/satellites/{{id}}/ccsds");
     println!("  - DELETE /api/satellites/{{id}}");
+    println!("  - GET    /api/satellites/{{id}}/passes");
+    println!("  - GET    /api/satellites/{{id}}/telemetry/limits");
+    println!("  - PUT    /api/satellites/{{id}}/telemetry/limits");
+    println!("  - GET    /api/dead-letters");
+    println!("  - GET    /api/dead-letters/{{id}}");
+    println!("  - POST   /api/dead-letters/{{id}}/replay");
+    println!("  - POST   /api/dead-letters/replay");
+    println!("  - GET    /api/alarms");
+    println!("  - GET    /api/alarms/{{id}}");
+    println!("  - POST   /api/alarms/{{id}}/acknowledge");
     println!("  - GET    /swagger-ui/");
     println!("Server running at: {}", server_address);
     println!("==============================================");
@@ -172,25 +359,59 @@ async fn main() -> std::io::Result<()> {
             .app_data(web::Data::new(ground_station_service.clone()))
             .app_data(web::Data::new(job_service.clone()))
             .app_data(web::Data::new(satellite_service.clone()))
+            .app_data(web::Data::new(tracking_service.clone()))
+            .app_data(web::Data::new(alarm_service.clone()))
+            .app_data(web::Data::new(dead_letter_service.clone()))
+            .app_data(web::Data::new(ingest_stats.clone()))
             // Telemetry
             .service(get_latest_telemetry)
             .service(get_historic_telemetry)
+            .service(get_aggregated_telemetry)
+            .service(export_telemetry)
+            .service(stream_live_telemetry)
+            .service(get_telemetry_receptions)
+            .service(get_telemetry_page)
+            .service(get_telemetry_decoder)
+            .service(set_telemetry_decoder)
+            .service(get_apid_decoder)
+            .service(set_apid_decoder)
             // Config & Control
             .service(get_config)
             .service(send_command)
+            .service(get_broker_status)
+            .service(get_ingest_metrics)
             // Ground Stations
             .service(create_ground_station)
             .service(fetch_all_ground_stations)
             .service(fetch_ground_station)
+            .service(fetch_ground_station_status)
             .service(set_tle_for_ground_station)
             // Jobs
             .service(create_job)
+            .service(fetch_all_jobs)
+            .service(fetch_job)
+            .service(fetch_job_status)
             // Satellites
             .service(fetch_all_satellites)
             .service(fetch_satellite)
             .service(create_satellite)
             .service(update_satellite_tle)
+            .service(get_ccsds_config)
+            .service(set_ccsds_config)
             .service(delete_satellite)
+            // Tracking
+            .service(get_satellite_passes)
+            .service(get_ground_station_passes)
+            // Alarms
+            .service(fetch_alarms)
+            .service(fetch_alarm)
+            .service(acknowledge_alarm)
+            .service(get_telemetry_limits)
+            .service(set_telemetry_limits)
+            .service(fetch_dead_letters)
+            .service(fetch_dead_letter)
+            .service(replay_dead_letter)
+            .service(replay_dead_letters)
             // Middleware & Docs
             .wrap(Logger::new("%r - %U | %s (%T)"))
             .service(
@@ -207,17 +428,19 @@ async fn main() -> std::io::Result<()> {
     let server_handle = server.run();
     let handle = server_handle.handle();
 
-    // Spawn MQTT receiver task
-    let recv_task = tokio::task::spawn_blocking(move || {
-        let rt =
-            tokio::runtime::Runtime::new().expect("Failed to create runtime for MQTT receiver");
-        rt.block_on(recv.run(shutdown_rx));
-    });
+    // Spawn MQTT receiver task. The event loop is not Sync, so it runs as a local task
+    // of the main arbiter.
+    let recv_task = actix_web::rt::spawn(async move { recv.run(shutdown_rx).await });
+    // Stops once the receiver has stopped and the queued messages are stored
+    let writer_task = actix_web::rt::spawn(ingest_writer.run());
 
     // Graceful shutdown on Ctrl+C
     tokio::select! {
         _ = signal::ctrl_c() => {
             println!("SIGINT received: shutting down server and MQTT receiver...");
+            if let Err(e) = presence_service.announce_offline() {
+                eprintln!("Error publishing presence: {:?}", e);
+            }
             let _ = shutdown_tx.send(());
             handle.stop(true).await;
         }
@@ -225,12 +448,16 @@ async fn main() -> std::io::Result<()> {
             if let Err(e) = res {
                 eprintln!("HTTP server error: {:?}", e);
             }
+            if let Err(e) = presence_service.announce_offline() {
+                eprintln!("Error publishing presence: {:?}", e);
+            }
             let _ = shutdown_tx.send(());
         }
     }
 
     // Wait for MQTT receiver to finish
     let _ = recv_task.await;
+    let _ = writer_task.await;
 
     Ok(())
 }
@@ -1,27 +1,76 @@
-use rumqttc::{AsyncClient, ClientError, EventLoop, MqttOptions, QoS};
+use crate::config::MessageBrokerConfig;
+use crate::messaging::connection::{BrokerConnection, ConnectionState};
+use log::{debug, warn};
+use rumqttc::tokio_rustls::rustls::{ClientConfig, RootCertStore};
+use rumqttc::{
+    AsyncClient, ClientError, EventLoop, LastWill, MqttOptions, QoS, TlsConfiguration, Transport,
+};
+use std::fmt;
+use std::sync::Arc;
 use std::time::Duration;
 use uuid::Uuid;
 
+/// State of the API retained on the presence topic, so ground stations can hold jobs
+/// locally while the control center is down
+#[derive(Debug, Clone, Copy)]
+pub enum Presence {
+    Online,
+    Offline,
+}
+
+impl Presence {
+    pub fn payload(self) -> &'static str {
+        match self {
+            Presence::Online => "online",
+            Presence::Offline => "offline",
+        }
+    }
+}
+
+/// Why a message could not be handed to the broker connection
+#[derive(Debug)]
+pub enum PublishError {
+    /// The broker is unreachable, so the message would wait for an unknown time
+    NotConnected,
+    /// The client's request queue is full or closed
+    Client(ClientError),
+}
+
+impl fmt::Display for PublishError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PublishError::NotConnected => write!(f, "not connected to the MQTT broker"),
+            PublishError::Client(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for PublishError {}
+
 pub struct MqttBroker {
     client: AsyncClient,
+    connection: BrokerConnection,
 }
 
 impl MqttBroker {
-    pub fn new(host: &str, port: u16, keep_alive: Duration) -> (Self, EventLoop) {
-        let client_id = format!("rustar-api-{}", Uuid::new_v4());
-        let mut options = MqttOptions::new(client_id, host, port);
-        options.set_keep_alive(keep_alive);
-        println!("connecting to broker {}:{}", host, port);
-
+    pub fn new(config: &MessageBrokerConfig) -> Result<(Self, EventLoop), String> {
+        let options = mqtt_options(config)?;
         let (client, eventloop) = AsyncClient::new(options, 10);
 
-        (Self { client }, eventloop)
+        Ok((
+            Self {
+                client,
+                connection: BrokerConnection::new(),
+            },
+            eventloop,
+        ))
     }
 
     #[allow(dead_code)]
     pub fn from_client(client: AsyncClient) -> Self {
         Self {
             client: client.clone(),
+            connection: BrokerConnection::new(),
         }
     }
 
@@ -29,11 +78,116 @@ impl MqttBroker {
         self.client.clone()
     }
 
-    pub async fn publish(&self, topic: &str, payload: &str) -> Result<(), ClientError> {
+    /// Connection state of the client, kept up to date by the receiver polling its event loop
+    pub fn connection(&self) -> BrokerConnection {
+        self.connection.clone()
+    }
+
+    /// Queue a message for the broker without waiting. Fails while the broker is not
+    /// connected or the request queue is full, rather than holding the caller until the
+    /// connection recovers. A queued message is sent by the event loop, with QoS 1
+    /// retries, but its delivery is not awaited.
+    pub fn publish(&self, topic: &str, payload: &str) -> Result<(), PublishError> {
+        if self.connection.status().state != ConnectionState::Connected {
+            return Err(PublishError::NotConnected);
+        }
         self.client
-            .publish(topic, QoS::AtLeastOnce, false, payload.as_bytes())
-            .await?;
-        println!("Published message {} to topic: {}", payload, topic);
+            .try_publish(topic, QoS::AtLeastOnce, false, payload.as_bytes())
+            .map_err(PublishError::Client)?;
+        debug!("Queued {} bytes for topic {}", payload.len(), topic);
         Ok(())
     }
+
+    /// Retain the API's presence on `topic`. The request is queued without waiting, as
+    /// it may be sent while the event loop is no longer polled.
+    pub fn publish_presence(&self, topic: &str, presence: Presence) -> Result<(), ClientError> {
+        publish_presence(&self.client, topic, presence)
+    }
+}
+
+pub fn publish_presence(
+    client: &AsyncClient,
+    topic: &str,
+    presence: Presence,
+) -> Result<(), ClientError> {
+    client.try_publish(topic, QoS::AtLeastOnce, true, presence.payload())?;
+    println!(
+        "Published presence {} to topic: {}",
+        presence.payload(),
+        topic
+    );
+    Ok(())
+}
+
+/// Connection options for the configured broker, with a unique client ID
+pub fn mqtt_options(config: &MessageBrokerConfig) -> Result<MqttOptions, String> {
+    let client_id = format!("rustar-api-{}", Uuid::new_v4());
+    let mut options = MqttOptions::new(client_id, &config.host, config.port);
+    options.set_keep_alive(Duration::from_secs(config.keep_alive as u64));
+    // Published by the broker if the API goes away without disconnecting
+    options.set_last_will(LastWill::new(
+        &config.topics.presence,
+        Presence::Offline.payload(),
+        QoS::AtLeastOnce,
+        true,
+    ));
+
+    match (&config.username, &config.password) {
+        (Some(username), password) => {
+            options.set_credentials(username, password.clone().unwrap_or_default());
+        }
+        (None, Some(_)) => return Err("A broker password requires a username".into()),
+        (None, None) => {}
+    }
+
+    let tls = &config.tls;
+    if tls.enabled {
+        let client_auth = match (&tls.cert, &tls.key) {
+            (Some(cert), Some(key)) => Some((read_pem(cert)?, read_pem(key)?)),
+            (None, None) => None,
+            _ => return Err("Broker TLS cert and key must be set together".into()),
+        };
+        let transport = match (&tls.ca, client_auth) {
+            (Some(ca), client_auth) => Transport::tls(read_pem(ca)?, client_auth, None),
+            (None, None) => Transport::tls_with_config(platform_tls()?),
+            (None, Some(_)) => {
+                return Err("Broker TLS client certificates require a CA certificate".into())
+            }
+        };
+        options.set_transport(transport);
+    } else if tls.ca.is_some() || tls.cert.is_some() || tls.key.is_some() {
+        return Err("Broker TLS certificates are set but TLS is not enabled".into());
+    }
+
+    println!(
+        "connecting to broker {}:{}{}",
+        config.host,
+        config.port,
+        if tls.enabled { " over TLS" } else { "" }
+    );
+    Ok(options)
+}
+
+/// TLS verified against the platform's root certificates. Unlike rumqttc's default
+/// configuration, missing or unreadable certificates are an error rather than a panic.
+fn platform_tls() -> Result<TlsConfiguration, String> {
+    let certs = rustls_native_certs::load_native_certs()
+        .map_err(|e| format!("Cannot load the platform's root certificates: {}", e))?;
+    let mut roots = RootCertStore::empty();
+    let (added, ignored) = roots.add_parsable_certificates(certs);
+    if added == 0 {
+        return Err("No usable root certificates found on the platform; set a broker CA".into());
+    }
+    if ignored > 0 {
+        warn!("Ignored {} unparsable platform root certificates", ignored);
+    }
+
+    let config = ClientConfig::builder()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    Ok(TlsConfiguration::Rustls(Arc::new(config)))
+}
+
+fn read_pem(path: &str) -> Result<Vec<u8>, String> {
+    std::fs::read(path).map_err(|e| format!("Cannot read {}: {}", path, e))
 }
@@ -0,0 +1,100 @@
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use utoipa::ToSchema;
+
+/// State of the connection to the MQTT broker
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionState {
+    Connecting,
+    Connected,
+    Disconnected,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ConnectionStatus {
+    pub state: ConnectionState,
+    /// When the connection entered its current state
+    pub since: DateTime<Utc>,
+}
+
+/// Connection state of the MQTT client, updated by the receiver driving its event loop
+/// and readable from anywhere in the app
+#[derive(Clone)]
+pub struct BrokerConnection {
+    status: Arc<watch::Sender<ConnectionStatus>>,
+}
+
+impl BrokerConnection {
+    pub fn new() -> Self {
+        let (status, _) = watch::channel(ConnectionStatus {
+            state: ConnectionState::Connecting,
+            since: Utc::now(),
+        });
+        Self {
+            status: Arc::new(status),
+        }
+    }
+
+    pub fn status(&self) -> ConnectionStatus {
+        *self.status.borrow()
+    }
+
+    /// Record a new state. Repeating the current state keeps its `since` time.
+    pub fn set(&self, state: ConnectionState) {
+        self.status.send_if_modified(|status| {
+            if status.state == state {
+                return false;
+            }
+            *status = ConnectionStatus {
+                state,
+                since: Utc::now(),
+            };
+            true
+        });
+    }
+
+    /// Watch the connection state as it changes
+    #[allow(dead_code)]
+    pub fn subscribe(&self) -> watch::Receiver<ConnectionStatus> {
+        self.status.subscribe()
+    }
+}
+
+/// Exponential reconnect delays with jitter, so stations and API instances that lost
+/// the broker together do not reconnect in lockstep
+pub struct Backoff {
+    initial: Duration,
+    max: Duration,
+    attempt: u32,
+}
+
+impl Backoff {
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Self {
+            initial,
+            max,
+            attempt: 0,
+        }
+    }
+
+    /// Delay before the next attempt: a random time between half and all of the
+    /// current exponential step
+    pub fn next_delay(&mut self) -> Duration {
+        let step = self
+            .initial
+            .saturating_mul(2u32.saturating_pow(self.attempt))
+            .min(self.max);
+        self.attempt = self.attempt.saturating_add(1);
+
+        step.mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+    }
+
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+    }
+}
@@ -0,0 +1,295 @@
+use crate::messaging::parser::parse_key_values;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+
+type ParseError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Newest envelope schema version the API understands. Stations may add fields to a
+/// version without bumping it; unknown fields are ignored.
+pub const ENVELOPE_VERSION: u32 = 1;
+
+/// Schema version reported for legacy `key:value|key:value` messages
+pub const LEGACY_VERSION: u32 = 0;
+
+/// A message published by a ground station, whatever its wire format
+#[derive(Debug, Clone)]
+pub struct Envelope {
+    pub message_type: Option<String>,
+    pub version: u32,
+    pub station_id: Option<i64>,
+    /// Unix time the station sent the message at
+    pub timestamp: Option<i64>,
+    /// Message fields, with every value as a string
+    pub body: HashMap<String, String>,
+}
+
+impl Envelope {
+    /// Flatten the envelope into the `key:value` fields the message parsers read. The
+    /// station becomes `gs_id` and the envelope timestamp is used when the body has none.
+    pub fn into_fields(self) -> Result<HashMap<String, String>, ParseError> {
+        let mut fields = self.body;
+
+        if let Some(message_type) = self.message_type {
+            fields.insert("type".into(), message_type);
+        }
+        if let Some(station_id) = self.station_id {
+            let station_id = station_id.to_string();
+            if fields
+                .get("gs_id")
+                .is_some_and(|gs_id| *gs_id != station_id)
+            {
+                return Err("Body gs_id does not match the envelope station_id".into());
+            }
+            fields.insert("gs_id".into(), station_id);
+        }
+        if let Some(timestamp) = self.timestamp {
+            fields
+                .entry("timestamp".into())
+                .or_insert_with(|| timestamp.to_string());
+        }
+
+        Ok(fields)
+    }
+}
+
+/// A wire format ground stations may publish messages in
+pub trait MessageFormat {
+    fn decode(&self, payload: &[u8]) -> Result<Envelope, ParseError>;
+}
+
+/// The original `key:value|key:value` text format, without an envelope
+pub struct PipeFormat;
+
+/// A JSON envelope:
+/// `{"type": "telemetry", "version": 1, "station_id": 1, "timestamp": 1761600000, "body": {...}}`
+pub struct JsonFormat;
+
+/// The JSON envelope layout encoded as CBOR. Byte strings in the body are hex-encoded.
+pub struct CborFormat;
+
+/// Decode a message in whichever format it was published in. JSON objects and CBOR
+/// maps are told apart from pipe-delimited text by their first byte.
+pub fn decode_message(payload: &[u8]) -> Result<Envelope, ParseError> {
+    let format: &dyn MessageFormat = match payload.first() {
+        Some(b'{') => &JsonFormat,
+        // CBOR major type 5: a map
+        Some(0xA0..=0xBF) => &CborFormat,
+        _ => &PipeFormat,
+    };
+    format.decode(payload)
+}
+
+impl MessageFormat for PipeFormat {
+    fn decode(&self, payload: &[u8]) -> Result<Envelope, ParseError> {
+        let mut body = parse_key_values(std::str::from_utf8(payload)?);
+
+        Ok(Envelope {
+            message_type: body.remove("type"),
+            version: LEGACY_VERSION,
+            station_id: None,
+            timestamp: None,
+            body,
+        })
+    }
+}
+
+impl MessageFormat for JsonFormat {
+    fn decode(&self, payload: &[u8]) -> Result<Envelope, ParseError> {
+        let raw: RawEnvelope<serde_json::Value> = serde_json::from_slice(payload)?;
+        raw.into_envelope(json_field)
+    }
+}
+
+impl MessageFormat for CborFormat {
+    fn decode(&self, payload: &[u8]) -> Result<Envelope, ParseError> {
+        let raw: RawEnvelope<ciborium::Value> = ciborium::from_reader(payload)?;
+        raw.into_envelope(cbor_field)
+    }
+}
+
+/// Envelope as found on the wire, with body values in the format's own value type
+#[derive(Deserialize)]
+struct RawEnvelope<V> {
+    #[serde(rename = "type")]
+    message_type: String,
+    version: u32,
+    station_id: Option<i64>,
+    timestamp: Option<i64>,
+    #[serde(default = "BTreeMap::new")]
+    body: BTreeMap<String, V>,
+}
+
+impl<V> RawEnvelope<V> {
+    fn into_envelope(
+        self,
+        field: fn(&str, V) -> Result<Option<String>, ParseError>,
+    ) -> Result<Envelope, ParseError> {
+        if self.version == LEGACY_VERSION || self.version > ENVELOPE_VERSION {
+            return Err(format!(
+                "Unsupported envelope version {} (supported: 1 to {})",
+                self.version, ENVELOPE_VERSION
+            )
+            .into());
+        }
+
+        let mut body = HashMap::new();
+        for (key, value) in self.body {
+            // Null fields are treated as absent
+            if let Some(value) = field(&key, value)? {
+                body.insert(key, value);
+            }
+        }
+
+        Ok(Envelope {
+            message_type: Some(self.message_type),
+            version: self.version,
+            station_id: self.station_id,
+            timestamp: self.timestamp,
+            body,
+        })
+    }
+}
+
+fn json_field(key: &str, value: serde_json::Value) -> Result<Option<String>, ParseError> {
+    use serde_json::Value;
+
+    match value {
+        Value::Null => Ok(None),
+        Value::Bool(b) => Ok(Some(b.to_string())),
+        Value::Number(n) => Ok(Some(n.to_string())),
+        Value::String(s) => Ok(Some(s)),
+        Value::Array(_) | Value::Object(_) => {
+            Err(format!("Body field '{}' must be a scalar", key).into())
+        }
+    }
+}
+
+fn cbor_field(key: &str, value: ciborium::Value) -> Result<Option<String>, ParseError> {
+    use ciborium::Value;
+
+    match value {
+        Value::Null => Ok(None),
+        Value::Bool(b) => Ok(Some(b.to_string())),
+        Value::Integer(i) => Ok(Some(i128::from(i).to_string())),
+        Value::Float(f) => Ok(Some(f.to_string())),
+        Value::Text(s) => Ok(Some(s)),
+        Value::Bytes(bytes) => Ok(Some(hex::encode(bytes))),
+        _ => Err(format!("Body field '{}' must be a scalar", key).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ciborium::Value;
+
+    fn cbor(version: u32, body: Vec<(&str, Value)>) -> Vec<u8> {
+        let envelope = Value::Map(vec![
+            (Value::Text("type".into()), Value::Text("frame".into())),
+            (
+                Value::Text("version".into()),
+                Value::Integer(version.into()),
+            ),
+            (Value::Text("station_id".into()), Value::Integer(2.into())),
+            (
+                Value::Text("body".into()),
+                Value::Map(
+                    body.into_iter()
+                        .map(|(key, value)| (Value::Text(key.into()), value))
+                        .collect(),
+                ),
+            ),
+        ]);
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&envelope, &mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn decodes_pipe_messages() {
+        let envelope = decode_message(b"type:telemetry|sat_id:1|voltage:3.3").unwrap();
+
+        assert_eq!(envelope.version, LEGACY_VERSION);
+        assert_eq!(envelope.message_type.as_deref(), Some("telemetry"));
+        assert_eq!(envelope.body["sat_id"], "1");
+        assert_eq!(envelope.body["voltage"], "3.3");
+        assert!(!envelope.body.contains_key("type"));
+    }
+
+    #[test]
+    fn decodes_json_envelopes() {
+        let payload = br#"{
+            "type": "telemetry", "version": 1, "station_id": 2, "timestamp": 1761600000,
+            "body": {"sat_id": 1, "voltage": 3.3, "valid": true, "note": "ok", "rssi": null}
+        }"#;
+        let envelope = decode_message(payload).unwrap();
+
+        assert_eq!(envelope.version, 1);
+        assert_eq!(envelope.station_id, Some(2));
+        assert_eq!(envelope.timestamp, Some(1761600000));
+        assert_eq!(envelope.body["sat_id"], "1");
+        assert_eq!(envelope.body["voltage"], "3.3");
+        assert_eq!(envelope.body["valid"], "true");
+        assert_eq!(envelope.body["note"], "ok");
+        assert!(!envelope.body.contains_key("rssi"));
+
+        let fields = envelope.into_fields().unwrap();
+        assert_eq!(fields["type"], "telemetry");
+        assert_eq!(fields["gs_id"], "2");
+        assert_eq!(fields["timestamp"], "1761600000");
+    }
+
+    #[test]
+    fn decodes_cbor_envelopes() {
+        let payload = cbor(
+            1,
+            vec![
+                ("sat_id", Value::Integer(1.into())),
+                ("data", Value::Bytes(vec![0xC0, 0x00, 0xAB])),
+                ("snr", Value::Float(4.5)),
+            ],
+        );
+        let envelope = decode_message(&payload).unwrap();
+
+        assert_eq!(envelope.message_type.as_deref(), Some("frame"));
+        assert_eq!(envelope.station_id, Some(2));
+        assert_eq!(envelope.body["sat_id"], "1");
+        assert_eq!(envelope.body["data"], "c000ab");
+        assert_eq!(envelope.body["snr"], "4.5");
+    }
+
+    #[test]
+    fn rejects_unsupported_versions() {
+        for version in [LEGACY_VERSION, ENVELOPE_VERSION + 1] {
+            let json = format!(
+                r#"{{"type": "telemetry", "version": {}, "body": {{}}}}"#,
+                version
+            );
+            assert!(decode_message(json.as_bytes()).is_err());
+            assert!(decode_message(&cbor(version, Vec::new())).is_err());
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_envelopes() {
+        // Missing version
+        assert!(decode_message(br#"{"type": "telemetry", "body": {}}"#).is_err());
+        // Nested body field
+        assert!(decode_message(br#"{"type": "frame", "version": 1, "body": {"a": [1]}}"#).is_err());
+        // Station in the body contradicting the envelope
+        let envelope = decode_message(
+            br#"{"type": "frame", "version": 1, "station_id": 2, "body": {"gs_id": 3}}"#,
+        )
+        .unwrap();
+        assert!(envelope.into_fields().is_err());
+    }
+
+    #[test]
+    fn body_timestamp_takes_precedence() {
+        let envelope = decode_message(
+            br#"{"type": "frame", "version": 1, "timestamp": 10, "body": {"timestamp": 20}}"#,
+        )
+        .unwrap();
+        assert_eq!(envelope.into_fields().unwrap()["timestamp"], "20");
+    }
+}
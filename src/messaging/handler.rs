@@ -0,0 +1,255 @@
+use crate::messaging::envelope::decode_message;
+use crate::messaging::parser::{
+    parse_frame, parse_heartbeat, parse_job_status, parse_legacy_frame, parse_reception,
+    parse_transfer_frame,
+};
+use crate::messaging::topics::{StationTopic, TopicScheme};
+use crate::models::{
+    entities::{JobStatusUpdate, StationHeartbeat, TelemetryFrame, TelemetryReception},
+    responses::TelemetryResponse,
+};
+use crate::services::{
+    alarm_service::AlarmService, errors::ServiceError,
+    ground_station_service::GroundStationService, job_service::JobService,
+    telemetry_service::TelemetryService,
+};
+use chrono::{DateTime, Utc};
+use log::debug;
+use std::{collections::HashMap, sync::Arc};
+
+pub type HandleError = Box<dyn std::error::Error + Send + Sync>;
+
+/// What a parsed message asks to store
+pub enum Inbound {
+    /// Frames received by a station. A CCSDS transfer frame yields one per space packet
+    /// it completes, possibly none.
+    Frames(Vec<(TelemetryFrame, TelemetryReception)>),
+    JobStatus(JobStatusUpdate),
+    Heartbeat(StationHeartbeat),
+}
+
+/// Where a message being processed comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Origin {
+    Broker,
+    Replay,
+}
+
+/// Processes the messages ground stations publish, both as they arrive and when a dead
+/// letter is replayed
+pub struct MessageHandler {
+    telemetry_service: Arc<TelemetryService>,
+    job_service: Arc<JobService>,
+    alarm_service: Arc<AlarmService>,
+    ground_station_service: Arc<GroundStationService>,
+    topics: Arc<TopicScheme>,
+}
+
+impl MessageHandler {
+    pub fn new(
+        telemetry_service: Arc<TelemetryService>,
+        job_service: Arc<JobService>,
+        alarm_service: Arc<AlarmService>,
+        ground_station_service: Arc<GroundStationService>,
+        topics: Arc<TopicScheme>,
+    ) -> Self {
+        Self {
+            telemetry_service,
+            job_service,
+            alarm_service,
+            ground_station_service,
+            topics,
+        }
+    }
+
+    /// Process a replayed message received on `topic` at `received_at` on its own. An
+    /// error means the message was rejected, could not be parsed or decoded, or could not
+    /// be stored.
+    pub async fn handle(
+        &self,
+        topic: &str,
+        payload: &[u8],
+        received_at: DateTime<Utc>,
+    ) -> Result<(), HandleError> {
+        match self
+            .parse_from(Origin::Replay, topic, payload, received_at)
+            .await?
+        {
+            Inbound::Frames(frames) => self.store_frames(&frames).await,
+            inbound => self.apply(inbound).await,
+        }
+    }
+
+    /// Parse a message received from the broker on `topic` into what it asks to store.
+    /// CCSDS transfer frames are broken into the space packets they complete.
+    pub async fn parse(
+        &self,
+        topic: &str,
+        payload: &[u8],
+        received_at: DateTime<Utc>,
+    ) -> Result<Inbound, HandleError> {
+        self.parse_from(Origin::Broker, topic, payload, received_at)
+            .await
+    }
+
+    /// Replayed transfer frames are broken into the packets they contain on their own,
+    /// as they would disturb the reassembly of live frames
+    async fn parse_from(
+        &self,
+        origin: Origin,
+        topic: &str,
+        payload: &[u8],
+        received_at: DateTime<Utc>,
+    ) -> Result<Inbound, HandleError> {
+        let station_topic = self
+            .topics
+            .parse(topic)
+            .ok_or_else(|| format!("Unknown topic {}", topic))?;
+
+        let envelope = decode_message(payload)?;
+        debug!(
+            "Message received on {} (schema version {}, {} bytes)",
+            topic,
+            envelope.version,
+            payload.len()
+        );
+        let mut map = envelope.into_fields()?;
+        apply_topic_identity(&mut map, station_topic)?;
+        let msg_type = map.get("type").map(|s| s.as_str()).unwrap_or("unknown");
+
+        let inbound = match (station_topic, msg_type) {
+            (StationTopic::Telemetry { .. }, "telemetry") => {
+                let frame = parse_legacy_frame(&map)?;
+                Inbound::Frames(vec![(frame, parse_reception(&map, received_at)?)])
+            }
+            (StationTopic::Telemetry { .. }, "frame")
+                if map.get("framing").is_some_and(|f| f == "ccsds") =>
+            {
+                let transfer_frame = parse_transfer_frame(&map, received_at)?;
+                let reception = parse_reception(&map, received_at)?;
+                let packets = match origin {
+                    Origin::Broker => {
+                        self.telemetry_service
+                            .extract_packets(&transfer_frame)
+                            .await?
+                    }
+                    Origin::Replay => {
+                        self.telemetry_service
+                            .extract_replayed_packets(&transfer_frame)
+                            .await?
+                    }
+                };
+                Inbound::Frames(
+                    packets
+                        .into_iter()
+                        .map(|packet| (packet, reception.clone()))
+                        .collect(),
+                )
+            }
+            (StationTopic::Telemetry { .. }, "frame") => {
+                let frame = parse_frame(&map, received_at)?;
+                Inbound::Frames(vec![(frame, parse_reception(&map, received_at)?)])
+            }
+            (StationTopic::Status { .. }, "job_status") => {
+                Inbound::JobStatus(parse_job_status(&map, received_at)?)
+            }
+            (StationTopic::Status { .. }, "heartbeat") => {
+                Inbound::Heartbeat(parse_heartbeat(&map, received_at)?)
+            }
+            (_, msg_type) => {
+                return Err(format!("Unexpected message type '{}' on {}", msg_type, topic).into())
+            }
+        };
+
+        Ok(inbound)
+    }
+
+    /// Store received frames in one batch and check the new ones against their
+    /// satellite's limits. Frames already stored from another station are not checked
+    /// again.
+    pub async fn store_frames(
+        &self,
+        frames: &[(TelemetryFrame, TelemetryReception)],
+    ) -> Result<(), HandleError> {
+        let samples = self.telemetry_service.save_frames(frames).await?;
+        for ((frame, _), sample) in frames.iter().zip(samples) {
+            if let Some(sample) = sample {
+                self.check_limits(&sample, frame.timestamp).await;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Apply a message other than frames, which are stored in batches
+    pub async fn apply(&self, inbound: Inbound) -> Result<(), HandleError> {
+        match inbound {
+            Inbound::Frames(frames) => self.store_frames(&frames).await?,
+            Inbound::JobStatus(update) => {
+                match self.job_service.record_status_update(&update).await {
+                    Ok(()) => println!("Job {} status updated: {:?}", update.job_id, update.status),
+                    // Kept in the job's status history with the rejection reason
+                    Err(ServiceError::Conflict(reason)) => {
+                        println!("Job status update rejected: {}", reason)
+                    }
+                    Err(e) => return Err(e.into()),
+                }
+            }
+            Inbound::Heartbeat(heartbeat) => {
+                self.ground_station_service
+                    .record_heartbeat(&heartbeat)
+                    .await?
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check a newly stored sample against its satellite's limits
+    async fn check_limits(&self, sample: &TelemetryResponse, timestamp: DateTime<Utc>) {
+        println!(
+            "Telemetry {} saved: satellite {}, ground station {}",
+            sample.id, sample.sat_id, sample.gs_id
+        );
+
+        // The frame is stored even if limit checking fails
+        if let Err(e) = self
+            .alarm_service
+            .check_sample(sample.sat_id, timestamp, &sample.parameters)
+            .await
+        {
+            eprintln!("Error checking telemetry limits: {}", e);
+        }
+    }
+}
+
+/// Fill in the station and satellite a message comes from from its topic. Messages on
+/// a telemetry topic are telemetry unless they say otherwise. A message naming another
+/// station or satellite than its topic is rejected.
+fn apply_topic_identity(
+    map: &mut HashMap<String, String>,
+    topic: StationTopic,
+) -> Result<(), String> {
+    let identity = match topic {
+        StationTopic::Telemetry { gs_id, sat_id } => {
+            map.entry("type".into())
+                .or_insert_with(|| "telemetry".into());
+            vec![("gs_id", gs_id), ("sat_id", sat_id)]
+        }
+        StationTopic::Status { gs_id } => vec![("gs_id", gs_id)],
+    };
+
+    for (key, id) in identity {
+        if let Some(value) = map.get(key) {
+            if value.parse::<i64>().ok() != Some(id) {
+                return Err(format!(
+                    "{} {} does not match the topic ({})",
+                    key, value, id
+                ));
+            }
+        }
+        // Normalized, so every station's copy of a frame has the same fingerprint
+        map.insert(key.to_string(), id.to_string());
+    }
+    Ok(())
+}
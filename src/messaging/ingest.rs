@@ -0,0 +1,213 @@
+use crate::config::IngestConfig;
+use crate::messaging::handler::{HandleError, Inbound, MessageHandler};
+use crate::services::dead_letter_service::DeadLetterService;
+use chrono::{DateTime, Utc};
+use std::ops::Range;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// A message received from the broker, waiting to be stored
+pub struct InboundMessage {
+    pub topic: String,
+    pub payload: Vec<u8>,
+    /// When the broker delivered the message. Receptions and messages without a
+    /// timestamp are stamped with it rather than with the time the batch is written.
+    pub received_at: DateTime<Utc>,
+}
+
+#[derive(Default)]
+struct Counters {
+    depth: AtomicUsize,
+    received: AtomicU64,
+    dropped: AtomicU64,
+    batches: AtomicU64,
+}
+
+/// Snapshot of the ingestion queue counters
+#[derive(Debug, Clone, Copy)]
+pub struct IngestMetrics {
+    pub queue_depth: usize,
+    pub queue_capacity: usize,
+    pub received: u64,
+    pub dropped: u64,
+    pub batches: u64,
+}
+
+/// Sending side of the ingestion queue, used by the MQTT receiver. The writer stops
+/// once every queue handle is dropped.
+pub struct IngestQueue {
+    sender: mpsc::Sender<InboundMessage>,
+    stats: IngestStats,
+}
+
+/// Read access to the ingestion queue counters
+#[derive(Clone)]
+pub struct IngestStats {
+    counters: Arc<Counters>,
+    capacity: usize,
+}
+
+/// Stores queued messages in batches
+pub struct IngestWriter {
+    receiver: mpsc::Receiver<InboundMessage>,
+    handler: Arc<MessageHandler>,
+    dead_letters: Arc<DeadLetterService>,
+    counters: Arc<Counters>,
+    batch_size: usize,
+    flush_interval: Duration,
+}
+
+/// Create the queue between the MQTT connection and the writer storing its messages
+pub fn ingest_queue(
+    config: &IngestConfig,
+    handler: Arc<MessageHandler>,
+    dead_letters: Arc<DeadLetterService>,
+) -> (IngestQueue, IngestWriter) {
+    let capacity = config.queue_capacity.max(1);
+    let (sender, receiver) = mpsc::channel(capacity);
+    let counters = Arc::new(Counters::default());
+
+    (
+        IngestQueue {
+            sender,
+            stats: IngestStats {
+                counters: counters.clone(),
+                capacity,
+            },
+        },
+        IngestWriter {
+            receiver,
+            handler,
+            dead_letters,
+            counters,
+            batch_size: config.batch_size.max(1),
+            flush_interval: Duration::from_millis(config.flush_interval_ms),
+        },
+    )
+}
+
+impl IngestQueue {
+    /// Queue a message without waiting, so a slow database never stalls the broker
+    /// connection. Returns false if the queue is full and the message was dropped.
+    pub fn push(&self, message: InboundMessage) -> bool {
+        let counters = &self.stats.counters;
+        counters.received.fetch_add(1, Ordering::Relaxed);
+        // Counted first, as the writer may take the message before `try_send` returns
+        counters.depth.fetch_add(1, Ordering::Relaxed);
+
+        if self.sender.try_send(message).is_err() {
+            counters.depth.fetch_sub(1, Ordering::Relaxed);
+            counters.dropped.fetch_add(1, Ordering::Relaxed);
+            return false;
+        }
+        true
+    }
+
+    pub fn stats(&self) -> IngestStats {
+        self.stats.clone()
+    }
+}
+
+impl IngestStats {
+    pub fn metrics(&self) -> IngestMetrics {
+        IngestMetrics {
+            queue_depth: self.counters.depth.load(Ordering::Relaxed),
+            queue_capacity: self.capacity,
+            received: self.counters.received.load(Ordering::Relaxed),
+            dropped: self.counters.dropped.load(Ordering::Relaxed),
+            batches: self.counters.batches.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl IngestWriter {
+    /// Store queued messages until the queue is closed. A batch is written once it is
+    /// full or its first message has waited for the flush interval.
+    pub async fn run(mut self) {
+        let mut batch = Vec::with_capacity(self.batch_size);
+
+        while let Some(message) = self.receiver.recv().await {
+            batch.push(message);
+
+            let flush = tokio::time::sleep(self.flush_interval);
+            tokio::pin!(flush);
+            while batch.len() < self.batch_size {
+                tokio::select! {
+                    message = self.receiver.recv() => match message {
+                        Some(message) => batch.push(message),
+                        None => break,
+                    },
+                    _ = &mut flush => break,
+                }
+            }
+
+            self.counters
+                .depth
+                .fetch_sub(batch.len(), Ordering::Relaxed);
+            self.write(std::mem::take(&mut batch)).await;
+        }
+
+        println!("Ingest writer: queue closed");
+    }
+
+    /// Store a batch. Telemetry frames of every message are inserted together; if that
+    /// fails they are stored message by message, so only the failing messages become
+    /// dead letters.
+    async fn write(&self, batch: Vec<InboundMessage>) {
+        let mut frames = Vec::new();
+        let mut framed: Vec<(InboundMessage, Range<usize>)> = Vec::new();
+
+        for message in batch {
+            let parsed = self
+                .handler
+                .parse(&message.topic, &message.payload, message.received_at)
+                .await;
+            match parsed {
+                Ok(Inbound::Frames(parsed)) => {
+                    let start = frames.len();
+                    frames.extend(parsed);
+                    framed.push((message, start..frames.len()));
+                }
+                Ok(inbound) => {
+                    if let Err(e) = self.handler.apply(inbound).await {
+                        self.dead_letter(&message, e).await;
+                    }
+                }
+                Err(e) => self.dead_letter(&message, e).await,
+            }
+        }
+
+        if let Err(e) = self.handler.store_frames(&frames).await {
+            eprintln!(
+                "Error storing a batch of {} frames, retrying message by message: {}",
+                frames.len(),
+                e
+            );
+            for (message, range) in framed {
+                if let Err(e) = self.handler.store_frames(&frames[range]).await {
+                    self.dead_letter(&message, e).await;
+                }
+            }
+        }
+
+        self.counters.batches.fetch_add(1, Ordering::Relaxed);
+    }
+
+    async fn dead_letter(&self, message: &InboundMessage, error: HandleError) {
+        eprintln!("Error handling message on {}: {}", message.topic, error);
+        if let Err(e) = self
+            .dead_letters
+            .record(
+                &message.topic,
+                &message.payload,
+                &error.to_string(),
+                message.received_at,
+            )
+            .await
+        {
+            eprintln!("Error storing dead letter: {}", e);
+        }
+    }
+}
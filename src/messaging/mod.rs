@@ -1,2 +1,8 @@
 pub mod broker;
+pub mod connection;
+pub mod envelope;
+pub mod handler;
+pub mod ingest;
+pub mod parser;
 pub mod receiver;
+pub mod topics;
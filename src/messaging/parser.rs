@@ -0,0 +1,281 @@
+use crate::framing::{ax25, kiss};
+use crate::models::entities::{
+    JobStatus, JobStatusUpdate, StationHeartbeat, TelemetryFrame, TelemetryReception, TransferFrame,
+};
+use chrono::{DateTime, Utc};
+use log::debug;
+use rustar_types::telemetry::TelemetryRecord;
+use std::collections::HashMap;
+
+/// Parse a `key:value|key:value` message into a map, treating every value as a string
+pub fn parse_key_values(msg: &str) -> HashMap<String, String> {
+    let mut map = HashMap::new();
+    for pair in msg.split('|') {
+        let pair = pair.trim();
+        if pair.is_empty() {
+            continue;
+        }
+        if let Some(idx) = pair.find(':') {
+            let key = pair[..idx].trim().to_string();
+            let value = pair[idx + 1..].trim().to_string();
+            map.insert(key, value);
+        }
+    }
+    map
+}
+
+/// Parse the satellite and ground station a message is attributed to
+pub fn parse_attribution(
+    map: &HashMap<String, String>,
+) -> Result<(i64, i64), Box<dyn std::error::Error + Send + Sync>> {
+    let sat_id = map
+        .get("sat_id")
+        .ok_or("Satellite ID not found")?
+        .parse::<i64>()?;
+    let gs_id = map
+        .get("gs_id")
+        .ok_or("Ground station ID not found")?
+        .parse::<i64>()?;
+    Ok((sat_id, gs_id))
+}
+
+/// Keys describing how a station received a frame rather than the frame itself
+const RECEPTION_KEYS: [&str; 4] = ["gs_id", "rssi", "snr", "frequency_offset"];
+
+/// Parse the reception metadata a station sends along with a frame, received by the
+/// API at `received_at`. Signal quality fields are optional.
+pub fn parse_reception(
+    map: &HashMap<String, String>,
+    received_at: DateTime<Utc>,
+) -> Result<TelemetryReception, Box<dyn std::error::Error + Send + Sync>> {
+    let optional = |key: &str| -> Result<Option<f64>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(map.get(key).map(|v| v.parse::<f64>()).transpose()?)
+    };
+
+    Ok(TelemetryReception {
+        gs_id: map
+            .get("gs_id")
+            .ok_or("Ground station ID not found")?
+            .parse::<i64>()?,
+        received_at,
+        rssi: optional("rssi")?,
+        snr: optional("snr")?,
+        frequency_offset: optional("frequency_offset")?,
+    })
+}
+
+/// Keys left out of a frame's fingerprint on top of the reception fields. Stations
+/// stamp the same frame with their own clocks, which the duplicate tolerance covers.
+const UNHASHED_KEYS: [&str; 2] = ["timestamp", "type"];
+
+/// Canonical `key:value` bytes of a message without the `excluded` keys
+fn canonical_fields(map: &HashMap<String, String>, excluded: &[&str]) -> Vec<u8> {
+    let mut pairs: Vec<_> = map
+        .iter()
+        .filter(|(key, _)| !excluded.contains(&key.as_str()))
+        .collect();
+    pairs.sort();
+
+    pairs
+        .into_iter()
+        .map(|(key, value)| format!("{}:{}", key, value))
+        .collect::<Vec<_>>()
+        .join("|")
+        .into_bytes()
+}
+
+pub fn parse_telemetry(
+    map: &HashMap<String, String>,
+) -> Result<TelemetryRecord, Box<dyn std::error::Error + Send + Sync>> {
+    let timestamp = map
+        .get("timestamp")
+        .ok_or("Timestamp not found")?
+        .parse::<i64>()?;
+    let temperature = map
+        .get("temperature")
+        .ok_or("Temperature not found")?
+        .parse::<f32>()?;
+    let voltage = map
+        .get("voltage")
+        .ok_or("Voltage not found")?
+        .parse::<f32>()?;
+    let current = map
+        .get("current")
+        .ok_or("Current not found")?
+        .parse::<f32>()?;
+    let battery_level = map
+        .get("battery_level")
+        .ok_or("Battery level not found")?
+        .parse::<i32>()?;
+    Ok(TelemetryRecord::new(
+        timestamp,
+        temperature,
+        voltage,
+        current,
+        battery_level,
+    ))
+}
+
+/// Parse a legacy telemetry message. The message is stored in its `key:value` form,
+/// without the reception fields, whatever format it was published in.
+pub fn parse_legacy_frame(
+    map: &HashMap<String, String>,
+) -> Result<TelemetryFrame, Box<dyn std::error::Error + Send + Sync>> {
+    let (sat_id, _) = parse_attribution(map)?;
+    // Validate the frame before storing its raw bytes
+    let telemetry = parse_telemetry(map)?;
+
+    // The fingerprint is identical for every station that received the same frame
+    let unhashed: Vec<&str> = RECEPTION_KEYS.into_iter().chain(UNHASHED_KEYS).collect();
+
+    Ok(TelemetryFrame {
+        sat_id,
+        timestamp: DateTime::from_timestamp(telemetry.timestamp, 0).ok_or("Invalid timestamp")?,
+        payload: canonical_fields(map, &RECEPTION_KEYS),
+        fingerprint: canonical_fields(map, &unhashed),
+        source_callsign: None,
+        destination_callsign: None,
+        apid: None,
+        sequence_count: None,
+    })
+}
+
+/// Parse a raw link-layer frame forwarded by a station. `data` holds the frame as hex,
+/// `framing` is `kiss` (default) or `ax25`, and `fcs` tells whether the frame ends with
+/// its FCS, which KISS TNCs usually strip. The AX.25 info field becomes the payload.
+pub fn parse_frame(
+    map: &HashMap<String, String>,
+    received_at: DateTime<Utc>,
+) -> Result<TelemetryFrame, Box<dyn std::error::Error + Send + Sync>> {
+    let sat_id = map
+        .get("sat_id")
+        .ok_or("Satellite ID not found")?
+        .parse::<i64>()?;
+    let timestamp = parse_optional_timestamp(map, received_at)?;
+    let data = hex::decode(map.get("data").ok_or("Frame data not found")?)?;
+
+    let (frame, fcs_default) = match map.get("framing").map(|s| s.as_str()).unwrap_or("kiss") {
+        "kiss" => (kiss::unwrap(&data)?, false),
+        "ax25" => (data, true),
+        other => return Err(format!("Unknown framing '{}'", other).into()),
+    };
+    let has_fcs = match map.get("fcs") {
+        Some(fcs) => fcs.parse::<bool>()?,
+        None => fcs_default,
+    };
+    let ax25 = ax25::parse(&frame, has_fcs)?;
+    debug!(
+        "AX.25 frame {} > {} via {:?}, PID {:#04x}, {} bytes",
+        ax25.source,
+        ax25.destination,
+        ax25.digipeaters
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<_>>(),
+        ax25.pid,
+        ax25.info.len()
+    );
+
+    // Stations may or may not forward the FCS, so it is not part of the fingerprint
+    let fingerprint = if has_fcs {
+        frame[..frame.len() - 2].to_vec()
+    } else {
+        frame
+    };
+
+    Ok(TelemetryFrame {
+        sat_id,
+        timestamp,
+        payload: ax25.info,
+        fingerprint,
+        source_callsign: Some(ax25.source.to_string()),
+        destination_callsign: Some(ax25.destination.to_string()),
+        apid: None,
+        sequence_count: None,
+    })
+}
+
+/// Parse a CCSDS TM transfer frame forwarded by a station. `data` holds the frame as
+/// hex; it is split into space packets with the satellite's CCSDS settings.
+pub fn parse_transfer_frame(
+    map: &HashMap<String, String>,
+    received_at: DateTime<Utc>,
+) -> Result<TransferFrame, Box<dyn std::error::Error + Send + Sync>> {
+    let (sat_id, gs_id) = parse_attribution(map)?;
+    let timestamp = parse_optional_timestamp(map, received_at)?;
+    let data = hex::decode(map.get("data").ok_or("Frame data not found")?)?;
+
+    Ok(TransferFrame {
+        sat_id,
+        gs_id,
+        timestamp,
+        data,
+    })
+}
+
+pub fn parse_job_status(
+    map: &HashMap<String, String>,
+    received_at: DateTime<Utc>,
+) -> Result<JobStatusUpdate, Box<dyn std::error::Error + Send + Sync>> {
+    let job_id = map
+        .get("job_id")
+        .ok_or("Job ID not found")?
+        .parse::<i64>()?;
+    let status = map
+        .get("status")
+        .ok_or("Status not found")?
+        .parse::<JobStatus>()?;
+    let gs_id = map
+        .get("gs_id")
+        .ok_or("Ground station ID not found")?
+        .parse::<i64>()?;
+    let timestamp = parse_optional_timestamp(map, received_at)?;
+    Ok(JobStatusUpdate {
+        gs_id: Some(gs_id),
+        ..JobStatusUpdate::new(job_id, timestamp, status)
+    })
+}
+
+/// Parse a heartbeat received at `received_at`. Every reported field is optional.
+pub fn parse_heartbeat(
+    map: &HashMap<String, String>,
+    received_at: DateTime<Utc>,
+) -> Result<StationHeartbeat, Box<dyn std::error::Error + Send + Sync>> {
+    let gs_id = map
+        .get("gs_id")
+        .ok_or("Ground station ID not found")?
+        .parse::<i64>()?;
+    let number = |key: &str| -> Result<Option<i64>, Box<dyn std::error::Error + Send + Sync>> {
+        match map.get(key) {
+            Some(value) => {
+                Ok(Some(value.parse::<i64>().map_err(|e| {
+                    format!("Invalid {} '{}': {}", key, value, e)
+                })?))
+            }
+            None => Ok(None),
+        }
+    };
+
+    Ok(StationHeartbeat {
+        gs_id,
+        timestamp: parse_optional_timestamp(map, received_at)?,
+        received_at,
+        software_version: map.get("software_version").cloned(),
+        uptime_secs: number("uptime")?,
+        rotator_status: map.get("rotator").cloned(),
+        radio_status: map.get("radio").cloned(),
+        disk_free_bytes: number("disk_free")?,
+    })
+}
+
+/// Stations without a synchronized clock may omit the timestamp, in which case the time
+/// the API received the message is used
+fn parse_optional_timestamp(
+    map: &HashMap<String, String>,
+    received_at: DateTime<Utc>,
+) -> Result<DateTime<Utc>, Box<dyn std::error::Error + Send + Sync>> {
+    match map.get("timestamp") {
+        Some(ts) => Ok(DateTime::from_timestamp(ts.parse::<i64>()?, 0).ok_or("Invalid timestamp")?),
+        None => Ok(received_at),
+    }
+}
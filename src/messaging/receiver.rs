@@ -1,53 +1,66 @@
-use crate::services::telemetry_service::TelemetryService;
+use crate::config::MessageBrokerConfig;
+use crate::messaging::broker::{mqtt_options, publish_presence, Presence};
+use crate::messaging::connection::{Backoff, BrokerConnection, ConnectionState};
+use crate::messaging::ingest::{InboundMessage, IngestQueue};
+use crate::messaging::topics::TopicScheme;
+use chrono::Utc;
+use log::{debug, trace};
 use rumqttc::{
     AsyncClient,
     Event::{self, Incoming, Outgoing},
-    EventLoop, MqttOptions,
-    Packet::Publish,
+    EventLoop,
+    Packet::{ConnAck, Publish},
     QoS,
 };
-use rustar_types::telemetry::TelemetryRecord;
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use std::{sync::Arc, time::Duration};
 use tokio::sync::oneshot;
-use uuid::Uuid;
+
+/// Delay before the first reconnect attempt after losing the broker
+const RECONNECT_INITIAL_DELAY: Duration = Duration::from_millis(500);
+/// Upper bound of the reconnect delay
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(30);
+/// How long a clean shutdown waits for the disconnect to reach the broker
+const DISCONNECT_TIMEOUT: Duration = Duration::from_secs(2);
 
 pub struct MqttReceiver {
     client: AsyncClient,
     eventloop: EventLoop,
-    telemetry_service: Arc<TelemetryService>,
+    topics: Arc<TopicScheme>,
+    queue: IngestQueue,
+    connection: BrokerConnection,
 }
 
 impl MqttReceiver {
     #[allow(dead_code)]
     pub fn new(
-        host: &str,
-        port: u16,
-        keep_alive: Duration,
-        telemetry_service: Arc<TelemetryService>,
-    ) -> Self {
-        let client_id = format!("rustar-api-{}", Uuid::new_v4());
-        let mut options = MqttOptions::new(client_id, host, port);
-        options.set_keep_alive(keep_alive);
-        println!("connecting to broker {}:{}", host, port);
+        config: &MessageBrokerConfig,
+        topics: Arc<TopicScheme>,
+        queue: IngestQueue,
+    ) -> Result<Self, String> {
+        let (client, eventloop) = AsyncClient::new(mqtt_options(config)?, 10);
 
-        let (client, eventloop) = AsyncClient::new(options, 10);
-
-        Self {
+        Ok(Self {
             client,
             eventloop,
-            telemetry_service,
-        }
+            topics,
+            queue,
+            connection: BrokerConnection::new(),
+        })
     }
 
     pub fn from_client(
         client: AsyncClient,
         eventloop: EventLoop,
-        telemetry_service: Arc<TelemetryService>,
+        topics: Arc<TopicScheme>,
+        queue: IngestQueue,
+        connection: BrokerConnection,
     ) -> Self {
         Self {
             client,
             eventloop,
-            telemetry_service,
+            topics,
+            queue,
+            connection,
         }
     }
 
@@ -56,131 +69,119 @@ impl MqttReceiver {
         self.client.clone()
     }
 
+    /// Poll the broker connection until shutdown. Lost connections are retried with
+    /// exponential backoff, and the subscriptions are renewed on every reconnect since
+    /// the broker does not keep them for a clean session.
     pub async fn run(&mut self, mut shutdown: oneshot::Receiver<()>) {
-        if let Err(e) = self.client.subscribe("test-topic", QoS::AtLeastOnce).await {
-            eprintln!("Error subscribing to topic: {:?}", e)
-        } else {
-            println!("Subscribed to topic: test-topic")
-        }
+        let mut backoff = Backoff::new(RECONNECT_INITIAL_DELAY, RECONNECT_MAX_DELAY);
 
         loop {
-            tokio::select! {
+            let event = tokio::select! {
                 _ = &mut shutdown => {
                     println!("MqttReceiver: shutdown signal received");
                     break;
                 }
-                event = self.eventloop.poll() => {
-                    match event {
-                        Ok(notif) => {
-                            if let Err(e) = self.handle_event(notif).await {
-                                eprintln!("Error handling event: {:?}", e);
-                            }
-                        },
-                        Err(e) => eprintln!("Connection error in recv: {:?}", e)
+                event = self.eventloop.poll() => event,
+            };
+
+            match event {
+                Ok(Incoming(ConnAck(ack))) => {
+                    println!("Connected to broker: {:?}", ack.code);
+                    backoff.reset();
+                    self.connection.set(ConnectionState::Connected);
+                    if let Err(e) =
+                        publish_presence(&self.client, self.topics.presence(), Presence::Online)
+                    {
+                        eprintln!("Error publishing presence: {:?}", e);
+                    }
+                    self.subscribe();
+                }
+                Ok(notif) => self.handle_event(notif),
+                Err(e) => {
+                    self.connection.set(ConnectionState::Disconnected);
+                    let delay = backoff.next_delay();
+                    eprintln!(
+                        "Broker connection error: {}. Reconnecting in {:?}",
+                        e, delay
+                    );
+                    tokio::select! {
+                        _ = &mut shutdown => {
+                            println!("MqttReceiver: shutdown signal received");
+                            break;
+                        }
+                        _ = tokio::time::sleep(delay) => {}
                     }
+                    self.connection.set(ConnectionState::Connecting);
                 }
             }
-            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
         }
 
-        if let Err(e) = self.client.disconnect().await {
-            eprintln!("Error disconnecting MQTT client: {:?}", e);
-        }
+        self.disconnect().await;
     }
 
-    async fn handle_event(
-        &self,
-        event: Event,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        println!("Notif: {:?}", event);
-
-        match event {
-            Incoming(pk) => {
-                println!("Received incoming event: {:?}", pk);
-
-                if let Publish(msg) = pk {
-                    let msg_text = String::from_utf8(msg.payload.to_vec());
-                    match msg_text {
-                        Ok(msg) => {
-                            println!("Message received:{:?}", msg);
-
-                            // Parse the message into a map of key-value pairs, treating everything as a string
-                            let mut map = std::collections::HashMap::new();
-                            for pair in msg.split('|') {
-                                let pair = pair.trim();
-                                if pair.is_empty() {
-                                    continue;
-                                }
-                                if let Some(idx) = pair.find(':') {
-                                    let key = pair[..idx].trim().to_string();
-                                    let value = pair[idx + 1..].trim().to_string();
-                                    map.insert(key, value);
-                                }
-                            }
-                            let msg_type = map.get("type").map(|s| s.as_str()).unwrap_or("unknown");
-
-                            match msg_type {
-                                "telemetry" => {
-                                    if let Ok(telemetry) = parse_telemetry(map) {
-                                        self.telemetry_service
-                                            .save_telemetry(
-                                                telemetry.timestamp,
-                                                telemetry.temperature,
-                                                telemetry.voltage,
-                                                telemetry.current,
-                                                telemetry.battery_level,
-                                            )
-                                            .await?;
-
-                                        println!("Telemetry saved: {:?}", telemetry);
-                                    } else {
-                                        eprintln!("Error saving telemetry");
-                                    }
-                                }
-                                _ => println!("Unknown message type. Message: {:?}", map),
-                            }
-                        }
-                        Err(e) => eprintln!("Error converting payload: {:?}", e),
-                    };
+    /// Subscribe to the station topics. The requests are queued from a separate task, as
+    /// the request channel only drains while the event loop is polled.
+    fn subscribe(&self) {
+        let client = self.client.clone();
+        let filters = self.topics.subscriptions();
+        tokio::spawn(async move {
+            for filter in filters {
+                if let Err(e) = client.subscribe(&filter, QoS::AtLeastOnce).await {
+                    eprintln!("Error subscribing to topic {}: {:?}", filter, e)
                 } else {
-                    println!("Incoming event: {:?}", pk)
+                    println!("Subscribed to topic: {}", filter)
                 }
             }
-            Outgoing(ev) => println!("Outgoing event: {:?}", ev),
+        });
+    }
+
+    /// Send a disconnect to the broker if connected, polling the event loop until it
+    /// goes out
+    async fn disconnect(&mut self) {
+        let connected = self.connection.status().state == ConnectionState::Connected;
+        self.connection.set(ConnectionState::Disconnected);
+        if !connected {
+            return;
         }
 
-        Ok(())
+        if let Err(e) = self.client.try_disconnect() {
+            eprintln!("Error disconnecting MQTT client: {:?}", e);
+            return;
+        }
+        let sent = tokio::time::timeout(DISCONNECT_TIMEOUT, async {
+            loop {
+                match self.eventloop.poll().await {
+                    Ok(Outgoing(rumqttc::Outgoing::Disconnect)) | Err(_) => break,
+                    Ok(_) => {}
+                }
+            }
+        })
+        .await;
+        if sent.is_err() {
+            eprintln!("Timed out disconnecting from the broker");
+        }
     }
-}
 
-fn parse_telemetry(
-    map: HashMap<String, String>,
-) -> Result<TelemetryRecord, Box<dyn std::error::Error + Send + Sync>> {
-    let timestamp = map
-        .get("timestamp")
-        .ok_or("Timestamp not found")?
-        .parse::<i64>()?;
-    let temperature = map
-        .get("temperature")
-        .ok_or("Temperature not found")?
-        .parse::<f32>()?;
-    let voltage = map
-        .get("voltage")
-        .ok_or("Voltage not found")?
-        .parse::<f32>()?;
-    let current = map
-        .get("current")
-        .ok_or("Current not found")?
-        .parse::<f32>()?;
-    let battery_level = map
-        .get("battery_level")
-        .ok_or("Battery level not found")?
-        .parse::<i32>()?;
-    Ok(TelemetryRecord::new(
-        timestamp,
-        temperature,
-        voltage,
-        current,
-        battery_level,
-    ))
+    /// Process an event of the connection. Messages are queued for the ingest writer.
+    fn handle_event(&self, event: Event) {
+        match event {
+            Incoming(Publish(msg)) => {
+                debug!(
+                    "Publish received on {} ({} bytes)",
+                    msg.topic,
+                    msg.payload.len()
+                );
+                let queued = self.queue.push(InboundMessage {
+                    topic: msg.topic.clone(),
+                    payload: msg.payload.to_vec(),
+                    received_at: Utc::now(),
+                });
+                if !queued {
+                    eprintln!("Ingest queue full, dropping message on {}", msg.topic);
+                }
+            }
+            Incoming(pk) => trace!("Incoming event: {:?}", pk),
+            Outgoing(ev) => trace!("Outgoing event: {:?}", ev),
+        }
+    }
 }
@@ -0,0 +1,259 @@
+use crate::config::TopicsConfig;
+
+const GS_ID: &str = "{gs_id}";
+const SAT_ID: &str = "{sat_id}";
+
+/// Identity carried by the topic of a message published by a ground station
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StationTopic {
+    Telemetry { gs_id: i64, sat_id: i64 },
+    Status { gs_id: i64 },
+}
+
+/// The configured MQTT topic layout. Patterns hold `{gs_id}` and `{sat_id}` placeholders
+/// as whole topic levels, which become `+` wildcards when subscribing.
+#[derive(Debug, Clone)]
+pub struct TopicScheme {
+    telemetry: Pattern,
+    status: Pattern,
+    jobs: Pattern,
+    commands: Pattern,
+    presence: String,
+}
+
+impl TopicScheme {
+    pub fn new(config: &TopicsConfig) -> Result<Self, String> {
+        Ok(Self {
+            telemetry: Pattern::parse(&config.telemetry, &[GS_ID, SAT_ID])?,
+            status: Pattern::parse(&config.status, &[GS_ID])?,
+            jobs: Pattern::parse(&config.jobs, &[GS_ID])?,
+            commands: Pattern::parse(&config.commands, &[GS_ID])?,
+            presence: Pattern::parse(&config.presence, &[]).map(|_| config.presence.clone())?,
+        })
+    }
+
+    /// Topic filters covering every station's telemetry and status messages
+    pub fn subscriptions(&self) -> [String; 2] {
+        [self.telemetry.filter(), self.status.filter()]
+    }
+
+    /// Work out which station, and satellite for telemetry, a message comes from
+    pub fn parse(&self, topic: &str) -> Option<StationTopic> {
+        if let Some(ids) = self.telemetry.matches(topic) {
+            return Some(StationTopic::Telemetry {
+                gs_id: ids.gs_id?,
+                sat_id: ids.sat_id?,
+            });
+        }
+        if let Some(ids) = self.status.matches(topic) {
+            return Some(StationTopic::Status { gs_id: ids.gs_id? });
+        }
+        None
+    }
+
+    /// Topic job descriptions are published to for a ground station
+    pub fn jobs(&self, gs_id: i64) -> String {
+        self.jobs.format(gs_id)
+    }
+
+    /// Topic commands are published to for a ground station
+    pub fn commands(&self, gs_id: i64) -> String {
+        self.commands.format(gs_id)
+    }
+
+    /// Topic the API's own online/offline state is retained on
+    pub fn presence(&self) -> &str {
+        &self.presence
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Level {
+    Literal(String),
+    GsId,
+    SatId,
+}
+
+#[derive(Debug, Clone)]
+struct Pattern(Vec<Level>);
+
+#[derive(Default)]
+struct TopicIds {
+    gs_id: Option<i64>,
+    sat_id: Option<i64>,
+}
+
+impl Pattern {
+    /// Parse a topic pattern, requiring every placeholder in `required` and no others
+    fn parse(pattern: &str, required: &[&str]) -> Result<Self, String> {
+        if pattern.is_empty() {
+            return Err("Topic pattern cannot be empty".into());
+        }
+
+        let mut levels = Vec::new();
+        for level in pattern.split('/') {
+            let parsed = match level {
+                GS_ID => Level::GsId,
+                SAT_ID => Level::SatId,
+                _ if level.contains(['+', '#', '{', '}']) => {
+                    return Err(format!(
+                        "Topic pattern '{}' has an invalid level '{}'",
+                        pattern, level
+                    ))
+                }
+                _ => Level::Literal(level.to_string()),
+            };
+            if !matches!(parsed, Level::Literal(_)) && levels.contains(&parsed) {
+                return Err(format!("Topic pattern '{}' repeats {}", pattern, level));
+            }
+            levels.push(parsed);
+        }
+
+        for (placeholder, level) in [(GS_ID, Level::GsId), (SAT_ID, Level::SatId)] {
+            let present = levels.contains(&level);
+            if required.contains(&placeholder) && !present {
+                return Err(format!(
+                    "Topic pattern '{}' must contain {}",
+                    pattern, placeholder
+                ));
+            }
+            if !required.contains(&placeholder) && present {
+                return Err(format!(
+                    "Topic pattern '{}' cannot contain {}",
+                    pattern, placeholder
+                ));
+            }
+        }
+
+        Ok(Self(levels))
+    }
+
+    fn filter(&self) -> String {
+        self.0
+            .iter()
+            .map(|level| match level {
+                Level::Literal(literal) => literal.as_str(),
+                Level::GsId | Level::SatId => "+",
+            })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    fn format(&self, gs_id: i64) -> String {
+        self.0
+            .iter()
+            .map(|level| match level {
+                Level::Literal(literal) => literal.clone(),
+                Level::GsId => gs_id.to_string(),
+                // Only telemetry patterns, which are never published to, hold a satellite
+                Level::SatId => unreachable!("station topic with a satellite level"),
+            })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    fn matches(&self, topic: &str) -> Option<TopicIds> {
+        let levels: Vec<&str> = topic.split('/').collect();
+        if levels.len() != self.0.len() {
+            return None;
+        }
+
+        let mut ids = TopicIds::default();
+        for (level, value) in self.0.iter().zip(levels) {
+            match level {
+                Level::Literal(literal) if literal == value => {}
+                Level::Literal(_) => return None,
+                Level::GsId => ids.gs_id = Some(value.parse().ok()?),
+                Level::SatId => ids.sat_id = Some(value.parse().ok()?),
+            }
+        }
+        Some(ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scheme() -> TopicScheme {
+        TopicScheme::new(&TopicsConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn subscribes_with_wildcards() {
+        assert_eq!(
+            scheme().subscriptions(),
+            ["stations/+/telemetry/+", "stations/+/status"]
+        );
+    }
+
+    #[test]
+    fn parses_station_topics() {
+        let topics = scheme();
+        assert_eq!(
+            topics.parse("stations/3/telemetry/7"),
+            Some(StationTopic::Telemetry {
+                gs_id: 3,
+                sat_id: 7
+            })
+        );
+        assert_eq!(
+            topics.parse("stations/3/status"),
+            Some(StationTopic::Status { gs_id: 3 })
+        );
+    }
+
+    #[test]
+    fn rejects_other_topics() {
+        let topics = scheme();
+        assert_eq!(topics.parse("stations/x/status"), None);
+        assert_eq!(topics.parse("stations/3/status/extra"), None);
+        assert_eq!(topics.parse("stations/3/telemetry"), None);
+        assert_eq!(topics.parse("stations/3/jobs"), None);
+        assert_eq!(topics.parse("other/3/status"), None);
+    }
+
+    #[test]
+    fn formatted_topics_round_trip() {
+        let pattern = Pattern::parse("gs/{gs_id}/up", &[GS_ID]).unwrap();
+        let topic = pattern.format(12);
+        assert_eq!(topic, "gs/12/up");
+        assert_eq!(pattern.matches(&topic).unwrap().gs_id, Some(12));
+        assert_eq!(pattern.filter(), "gs/+/up");
+
+        let pattern = Pattern::parse("{sat_id}/tm/{gs_id}", &[GS_ID, SAT_ID]).unwrap();
+        let ids = pattern.matches("5/tm/6").unwrap();
+        assert_eq!((ids.gs_id, ids.sat_id), (Some(6), Some(5)));
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        for (pattern, required) in [
+            ("", &[GS_ID][..]),
+            ("stations/+/status", &[GS_ID]),
+            ("stations/#", &[GS_ID]),
+            ("stations/gs{gs_id}", &[GS_ID]),
+            ("stations/{gs_id}/{gs_id}", &[GS_ID]),
+            ("stations/status", &[GS_ID]),
+            ("stations/{gs_id}/{sat_id}", &[GS_ID]),
+            ("stations/{other}", &[GS_ID]),
+        ] {
+            assert!(
+                Pattern::parse(pattern, required).is_err(),
+                "{} should be rejected",
+                pattern
+            );
+        }
+    }
+
+    #[test]
+    fn presence_topic_has_no_placeholders() {
+        assert_eq!(scheme().presence(), "api/presence");
+
+        let config = TopicsConfig {
+            presence: "api/{gs_id}/presence".into(),
+            ..TopicsConfig::default()
+        };
+        assert!(TopicScheme::new(&config).is_err());
+    }
+}
@@ -6,3 +6,29 @@ pub struct TestMessage {
     pub number: i32,
     pub message: String,
 }
+
+/// Version of the job description sent to ground stations. Bump when the
+/// layout of `JobMessage` changes in a way stations need to know about.
+pub const JOB_MESSAGE_VERSION: u32 = 1;
+
+/// Satellite parameters a ground station needs to track and talk to it
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct JobSatellite {
+    pub id: i64,
+    pub name: String,
+    pub tle: String,
+    pub downlink_frequency: f64,
+    pub uplink_frequency: f64,
+}
+
+/// Job description published to a ground station
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct JobMessage {
+    pub version: u32,
+    pub job_id: i64,
+    pub gs_id: i64,
+    pub start: i64, // unix timestamp (AOS)
+    pub end: i64,   // unix timestamp (LOS)
+    pub satellite: JobSatellite,
+    pub commands: Vec<String>,
+}
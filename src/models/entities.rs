@@ -1,8 +1,10 @@
 use crate::models::requests::{GroundStationCreateRequest, SatelliteCreateRequest};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use utoipa::ToSchema;
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, ToSchema)]
 #[sqlx(type_name = "job_status", rename_all = "PascalCase")]
 pub enum JobStatus {
     Sent,
@@ -12,6 +14,21 @@ pub enum JobStatus {
     Error,
 }
 
+impl FromStr for JobStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "sent" => Ok(JobStatus::Sent),
+            "received" => Ok(JobStatus::Received),
+            "started" => Ok(JobStatus::Started),
+            "completed" => Ok(JobStatus::Completed),
+            "error" => Ok(JobStatus::Error),
+            _ => Err(format!("Unknown job status '{}'", s)),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct GroundStation {
     pub id: i64,
@@ -35,7 +52,7 @@ impl GroundStation {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, sqlx::FromRow)]
 pub struct Satellite {
     pub id: i64,
     pub name: String,
@@ -56,32 +73,251 @@ impl Satellite {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct Job {
     pub id: i64,
     pub gs_id: i64,
     pub sat_id: i64,
-    pub start_time: i32,
-    pub end_time: i32,
+    pub start_time: i64,
+    pub end_time: i64,
     pub commands: Vec<String>,
 }
 
 impl Job {
-    pub fn new(gs_id: &i64, sat_id: &i64, commands: &Vec<String>) -> Self {
+    pub fn new(
+        gs_id: &i64,
+        sat_id: &i64,
+        start_time: i64,
+        end_time: i64,
+        commands: &Vec<String>,
+    ) -> Self {
         Self {
             id: 0,
             gs_id: *gs_id,
             sat_id: *sat_id,
-            start_time: 0,
-            end_time: 0,
+            start_time,
+            end_time,
             commands: commands.clone(),
         }
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, sqlx::FromRow)]
 pub struct JobStatusUpdate {
     pub job_id: i64,
     pub timestamp: DateTime<Utc>,
     pub status: JobStatus,
+    /// Set when the job state machine rejected the update
+    pub rejection_reason: Option<String>,
+    /// Ground station that reported the update, which must be the job's station. Unset
+    /// for updates recorded by the API itself.
+    #[serde(skip)]
+    #[sqlx(skip)]
+    pub gs_id: Option<i64>,
+}
+
+impl JobStatusUpdate {
+    pub fn new(job_id: i64, timestamp: DateTime<Utc>, status: JobStatus) -> Self {
+        Self {
+            job_id,
+            timestamp,
+            status,
+            rejection_reason: None,
+            gs_id: None,
+        }
+    }
+}
+
+/// Byte order of a multi-byte telemetry field
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Endianness {
+    #[default]
+    Big,
+    Little,
+}
+
+/// Location and calibration of a single parameter inside a telemetry frame.
+///
+/// The engineering value is `raw * scale + offset`, where `raw` is the integer
+/// read from `width` bytes starting at `byte_offset`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DecoderField {
+    #[schema(example = "battery_voltage")]
+    pub name: String,
+    #[schema(example = 4)]
+    pub byte_offset: usize,
+    /// Field width in bytes: 1, 2, 4 or 8
+    #[schema(example = 2)]
+    pub width: u8,
+    #[serde(default)]
+    pub endianness: Endianness,
+    #[serde(default)]
+    pub signed: bool,
+    #[serde(default = "default_scale")]
+    #[schema(example = 0.001)]
+    pub scale: f64,
+    #[serde(default)]
+    pub offset: f64,
+    #[schema(example = "V")]
+    pub unit: Option<String>,
+    /// Values below this bound are reported as invalid
+    #[schema(example = 6.0)]
+    pub valid_min: Option<f64>,
+    /// Values above this bound are reported as invalid
+    #[schema(example = 8.4)]
+    pub valid_max: Option<f64>,
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+/// Declarative decoder turning a satellite's raw frames into named engineering values
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TelemetryDecoder {
+    pub fields: Vec<DecoderField>,
+}
+
+/// CCSDS TM transfer frame settings of a satellite
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct CcsdsConfig {
+    /// Spacecraft identifier expected in the transfer frame header
+    #[schema(example = 42)]
+    pub spacecraft_id: i32,
+    /// Whether frames end with a frame error control field
+    #[serde(default = "default_fecf")]
+    pub fecf: bool,
+}
+
+fn default_fecf() -> bool {
+    true
+}
+
+/// Limits of one telemetry parameter. Values beyond a red limit raise a red alarm,
+/// values beyond a yellow limit a yellow one. Unset limits are not checked.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetryLimit {
+    #[schema(example = "battery_level")]
+    pub parameter: String,
+    #[schema(example = 10.0)]
+    pub red_low: Option<f64>,
+    #[schema(example = 25.0)]
+    pub yellow_low: Option<f64>,
+    pub yellow_high: Option<f64>,
+    pub red_high: Option<f64>,
+    /// Consecutive violating samples required before an alarm is raised
+    #[serde(default = "default_persistence")]
+    #[schema(example = 3)]
+    pub persistence: i32,
+}
+
+fn default_persistence() -> i32 {
+    1
+}
+
+impl TelemetryLimit {
+    /// Severity of the limit a value violates, if any
+    pub fn check(&self, value: f64) -> Option<AlarmSeverity> {
+        let below = |limit: Option<f64>| limit.is_some_and(|l| value < l);
+        let above = |limit: Option<f64>| limit.is_some_and(|l| value > l);
+
+        if below(self.red_low) || above(self.red_high) {
+            Some(AlarmSeverity::Red)
+        } else if below(self.yellow_low) || above(self.yellow_high) {
+            Some(AlarmSeverity::Yellow)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, sqlx::Type, ToSchema,
+)]
+#[sqlx(type_name = "alarm_severity", rename_all = "PascalCase")]
+pub enum AlarmSeverity {
+    Yellow,
+    Red,
+}
+
+/// A limit violation. It is active until the parameter is back within limits.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct Alarm {
+    pub id: i64,
+    pub sat_id: i64,
+    pub parameter: String,
+    pub severity: AlarmSeverity,
+    pub value: f64,
+    pub raised_at: DateTime<Utc>,
+    pub cleared_at: Option<DateTime<Utc>>,
+    pub acknowledged_at: Option<DateTime<Utc>>,
+    pub acknowledged_by: Option<String>,
+}
+
+/// Latest state a ground station reported in a heartbeat
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct StationHeartbeat {
+    pub gs_id: i64,
+    /// When the station sent the heartbeat
+    pub timestamp: DateTime<Utc>,
+    /// When the API received it
+    pub received_at: DateTime<Utc>,
+    pub software_version: Option<String>,
+    pub uptime_secs: Option<i64>,
+    pub rotator_status: Option<String>,
+    pub radio_status: Option<String>,
+    pub disk_free_bytes: Option<i64>,
+}
+
+/// An inbound message that failed parsing, decoding or persistence
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct DeadLetter {
+    pub id: i64,
+    pub topic: String,
+    pub payload: Vec<u8>,
+    pub error: String,
+    pub received_at: DateTime<Utc>,
+    pub replay_attempts: i32,
+    pub replayed_at: Option<DateTime<Utc>>,
+}
+
+/// How a ground station received a telemetry frame
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct TelemetryReception {
+    pub gs_id: i64,
+    pub received_at: DateTime<Utc>,
+    pub rssi: Option<f64>,
+    pub snr: Option<f64>,
+    pub frequency_offset: Option<f64>,
+}
+
+/// A received telemetry frame ready to be stored
+#[derive(Debug, Clone)]
+pub struct TelemetryFrame {
+    pub sat_id: i64,
+    pub timestamp: DateTime<Utc>,
+    /// Bytes handed to the satellite's telemetry decoder
+    pub payload: Vec<u8>,
+    /// Bytes identifying the frame regardless of the station that received it
+    pub fingerprint: Vec<u8>,
+    pub source_callsign: Option<String>,
+    pub destination_callsign: Option<String>,
+    /// Space packet identification of frames extracted from CCSDS transfer frames
+    pub apid: Option<i32>,
+    pub sequence_count: Option<i32>,
+}
+
+/// A CCSDS TM transfer frame received by a station, before it is split into packets
+#[derive(Debug, Clone)]
+pub struct TransferFrame {
+    pub sat_id: i64,
+    /// Station that received the frame, whose copies of a virtual channel are
+    /// reassembled separately
+    pub gs_id: i64,
+    pub timestamp: DateTime<Utc>,
+    pub data: Vec<u8>,
 }
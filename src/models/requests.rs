@@ -11,14 +11,159 @@ pub struct HistoricTelemetryRequest {
     pub start_time: Option<i64>,
     #[param(example = 1640998800)]
     pub end_time: Option<i64>,
+    pub raw: Option<RawEncoding>,
 }
 
-#[derive(ToSchema, IntoParams, Debug, Deserialize)]
+#[derive(ToSchema, IntoParams, Debug, Deserialize, Validate)]
 #[into_params(style = Form)]
 #[serde(rename_all = "camelCase")]
 pub struct LatestTelemetryRequest {
+    /// Number of samples to return (defaults to 10)
+    #[validate(range(min = 1, max = 1000, message = "Amount must be between 1 and 1000"))]
     #[param(example = 10)]
     pub amount: Option<i32>,
+    pub raw: Option<RawEncoding>,
+}
+
+#[derive(ToSchema, IntoParams, Debug, Deserialize, Validate)]
+#[into_params(style = Form)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetryPageRequest {
+    /// Number of samples per page (defaults to 100)
+    #[validate(range(min = 1, max = 1000, message = "Page size must be between 1 and 1000"))]
+    #[param(example = 100)]
+    pub page_size: Option<i64>,
+    /// Opaque cursor returned as `next_cursor` by the previous page
+    pub cursor: Option<String>,
+    /// Start of the time range (unix timestamp)
+    #[param(example = 1640995200)]
+    pub start_time: Option<i64>,
+    /// End of the time range (unix timestamp, defaults to now)
+    #[param(example = 1640998800)]
+    pub end_time: Option<i64>,
+    /// Only return frames received by this ground station
+    #[param(example = 1)]
+    pub gs_id: Option<i64>,
+    /// Comma-separated parameter names to return
+    #[param(example = "battery_voltage,temperature")]
+    pub parameters: Option<String>,
+    pub order: Option<SortOrder>,
+    pub raw: Option<RawEncoding>,
+}
+
+#[derive(ToSchema, IntoParams, Debug, Deserialize, Validate)]
+#[into_params(style = Form)]
+#[serde(rename_all = "camelCase")]
+pub struct JobListRequest {
+    /// Number of jobs per page (defaults to 100)
+    #[validate(range(min = 1, max = 1000, message = "Page size must be between 1 and 1000"))]
+    #[param(example = 100)]
+    pub page_size: Option<i64>,
+    /// Opaque cursor returned as `next_cursor` by the previous page
+    pub cursor: Option<String>,
+}
+
+#[derive(ToSchema, IntoParams, Debug, Deserialize, Validate)]
+#[into_params(style = Form)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetryAggregateRequest {
+    /// Bucket width in seconds
+    #[validate(range(min = 1, message = "Interval must be at least one second"))]
+    #[param(example = 3600)]
+    pub interval: i64,
+    /// Start of the time range (unix timestamp, defaults to 31 days before the end)
+    #[param(example = 1640995200)]
+    pub start_time: Option<i64>,
+    /// End of the time range (unix timestamp, defaults to now)
+    #[param(example = 1643673600)]
+    pub end_time: Option<i64>,
+    /// Only aggregate frames received by this ground station
+    #[param(example = 1)]
+    pub gs_id: Option<i64>,
+    /// Comma-separated parameter names to aggregate
+    #[param(example = "battery_voltage")]
+    pub parameters: Option<String>,
+}
+
+#[derive(ToSchema, IntoParams, Debug, Deserialize)]
+#[into_params(style = Form)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetryExportRequest {
+    /// Output format, overriding the `Accept` header
+    pub format: Option<ExportFormat>,
+    /// Start of the time range (unix timestamp)
+    #[param(example = 1640995200)]
+    pub start_time: Option<i64>,
+    /// End of the time range (unix timestamp, defaults to now)
+    #[param(example = 1672531200)]
+    pub end_time: Option<i64>,
+    /// Only export frames received by this ground station
+    #[param(example = 1)]
+    pub gs_id: Option<i64>,
+    /// Comma-separated parameter names to export
+    #[param(example = "battery_voltage,temperature")]
+    pub parameters: Option<String>,
+    /// Encoding of the raw frame column (defaults to hex)
+    pub raw: Option<RawEncoding>,
+}
+
+/// Telemetry export file format
+#[derive(ToSchema, Debug, Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Ndjson,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv",
+            ExportFormat::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Ndjson => "ndjson",
+        }
+    }
+}
+
+#[derive(ToSchema, IntoParams, Debug, Deserialize)]
+#[into_params(style = Form)]
+#[serde(rename_all = "camelCase")]
+pub struct ControlCommandRequest {
+    /// Ground station the command is sent to
+    #[param(example = 1)]
+    pub gs_id: i64,
+}
+
+#[derive(ToSchema, IntoParams, Debug, Deserialize)]
+#[into_params(style = Form)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveTelemetryRequest {
+    /// Comma-separated parameter names to stream
+    #[param(example = "battery_voltage,temperature")]
+    pub parameters: Option<String>,
+}
+
+/// Ordering of telemetry samples by time
+#[derive(ToSchema, Debug, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+/// Encoding of the raw frame included in telemetry responses
+#[derive(ToSchema, Debug, Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum RawEncoding {
+    Hex,
+    Base64,
 }
 
 #[derive(ToSchema, IntoParams, Debug, Deserialize)]
@@ -42,6 +187,10 @@ pub struct JobCreateRequest {
     pub gs_id: i64,
     #[param(example = json!(["command1", "command2"]))]
     pub commands: Vec<String>,
+    /// AOS of the pass to schedule (unix timestamp, as returned by the passes endpoints).
+    /// Defaults to the next pass of the satellite over the ground station.
+    #[param(example = 1640995200)]
+    pub aos: Option<i64>,
 }
 
 #[derive(ToSchema, IntoParams, Debug, Deserialize, Validate)]
@@ -77,3 +226,75 @@ pub struct TleUpdateRequest {
     )]
     pub tle: String,
 }
+
+#[derive(ToSchema, IntoParams, Debug, Deserialize, Validate)]
+#[into_params(style = Form)]
+#[serde(rename_all = "camelCase")]
+pub struct PassPredictionRequest {
+    /// Start of the prediction window (unix timestamp, defaults to now)
+    #[param(example = 1640995200)]
+    pub start_time: Option<i64>,
+    /// End of the prediction window (unix timestamp, defaults to 24 hours after start)
+    #[param(example = 1641081600)]
+    pub end_time: Option<i64>,
+    /// Minimum peak elevation in degrees for a pass to be reported
+    #[validate(range(
+        min = 0.0,
+        max = 90.0,
+        message = "Minimum elevation must be between 0 and 90 degrees"
+    ))]
+    #[param(example = 10.0)]
+    pub min_elevation: Option<f64>,
+}
+
+#[derive(ToSchema, IntoParams, Debug, Deserialize, Validate)]
+#[into_params(style = Form)]
+#[serde(rename_all = "camelCase")]
+pub struct AlarmListRequest {
+    /// Only alarms of this satellite
+    #[param(example = 1)]
+    pub sat_id: Option<i64>,
+    /// Only active (`true`) or cleared (`false`) alarms
+    pub active: Option<bool>,
+    /// Only acknowledged (`true`) or unacknowledged (`false`) alarms
+    pub acknowledged: Option<bool>,
+    /// Maximum number of alarms to return (defaults to 100)
+    #[validate(range(min = 1, max = 1000, message = "Limit must be between 1 and 1000"))]
+    #[param(example = 100)]
+    pub limit: Option<i64>,
+}
+
+#[derive(ToSchema, IntoParams, Debug, Deserialize, Validate)]
+#[into_params(style = Form)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadLetterListRequest {
+    /// Only dead letters received on this topic
+    #[param(example = "stations/1/telemetry/1")]
+    pub topic: Option<String>,
+    /// Only replayed (`true`) or pending (`false`) dead letters
+    pub replayed: Option<bool>,
+    /// Maximum number of dead letters to return (defaults to 100)
+    #[validate(range(min = 1, max = 1000, message = "Limit must be between 1 and 1000"))]
+    #[param(example = 100)]
+    pub limit: Option<i64>,
+}
+
+#[derive(ToSchema, IntoParams, Debug, Deserialize, Validate)]
+#[into_params(style = Form)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadLetterReplayRequest {
+    /// Only replay dead letters received on this topic
+    #[param(example = "stations/1/telemetry/1")]
+    pub topic: Option<String>,
+    /// Maximum number of dead letters to replay (defaults to 100)
+    #[validate(range(min = 1, max = 1000, message = "Limit must be between 1 and 1000"))]
+    #[param(example = 100)]
+    pub limit: Option<i64>,
+}
+
+#[derive(ToSchema, Debug, Deserialize, Validate)]
+pub struct AlarmAcknowledgeRequest {
+    #[validate(length(min = 1, message = "Operator cannot be empty"))]
+    #[schema(example = "jdoe")]
+    pub operator: String,
+}
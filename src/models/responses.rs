@@ -1,13 +1,99 @@
+use crate::messaging::connection::{ConnectionState, ConnectionStatus};
+use crate::messaging::ingest::IngestMetrics;
+use crate::models::entities::{
+    Alarm, AlarmSeverity, DeadLetter, GroundStation, Job, JobStatus, JobStatusUpdate,
+    StationHeartbeat, TelemetryReception,
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use serde::Serialize;
+use std::collections::BTreeMap;
 use utoipa::ToSchema;
 
-#[derive(ToSchema, Debug, Serialize)]
+#[derive(ToSchema, Debug, Clone, Serialize)]
 pub struct TelemetryResponse {
+    pub id: i64,
     pub timestamp: i64, // ISO timestamp
-    pub temperature: f32,
-    pub voltage: f32,
-    pub current: f32,
-    pub battery_level: i32, // percentage
+    pub sat_id: i64,
+    pub gs_id: i64,       // first ground station that received the frame
+    pub gs_ids: Vec<i64>, // every ground station that received the frame
+    pub source_callsign: Option<String>, // AX.25 addresses of frames received as AX.25
+    pub destination_callsign: Option<String>,
+    pub apid: Option<i32>, // space packet identification of frames received as CCSDS
+    pub sequence_count: Option<i32>,
+    pub parameters: BTreeMap<String, TelemetryParameter>, // decoded values by parameter name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw: Option<String>, // frame as received, only when requested
+}
+
+/// How a ground station received a telemetry frame
+#[derive(ToSchema, Debug, Serialize)]
+pub struct TelemetryReceptionResponse {
+    pub gs_id: i64,
+    pub received_at: i64, // unix timestamp
+    pub rssi: Option<f64>,
+    pub snr: Option<f64>,
+    pub frequency_offset: Option<f64>,
+}
+
+impl From<TelemetryReception> for TelemetryReceptionResponse {
+    fn from(reception: TelemetryReception) -> Self {
+        Self {
+            gs_id: reception.gs_id,
+            received_at: reception.received_at.timestamp(),
+            rssi: reception.rssi,
+            snr: reception.snr,
+            frequency_offset: reception.frequency_offset,
+        }
+    }
+}
+
+/// A page of telemetry samples. `next_cursor` is absent on the last page.
+#[derive(ToSchema, Debug, Serialize)]
+pub struct TelemetryPageResponse {
+    pub items: Vec<TelemetryResponse>,
+    pub next_cursor: Option<String>,
+}
+
+/// A page of jobs, most recent first. `next_cursor` is absent on the last page.
+#[derive(ToSchema, Debug, Serialize)]
+pub struct JobPageResponse {
+    pub items: Vec<Job>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(ToSchema, Debug, Serialize)]
+pub struct TelemetryAggregateResponse {
+    pub sat_id: i64,
+    pub interval: i64, // bucket width in seconds
+    pub buckets: Vec<TelemetryBucket>,
+}
+
+/// Aggregates of the valid values received in `[start, end)`. Empty buckets are omitted.
+#[derive(ToSchema, Debug, Serialize)]
+pub struct TelemetryBucket {
+    pub start: i64,
+    pub end: i64,
+    pub parameters: BTreeMap<String, ParameterAggregate>,
+}
+
+#[derive(ToSchema, Debug, Serialize)]
+pub struct ParameterAggregate {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub count: i64,
+    pub last: f64, // most recent value in the bucket
+    pub unit: Option<String>,
+}
+
+/// A decoded telemetry value. Invalid values are missing from the frame or out of range.
+#[derive(ToSchema, Debug, Clone, Serialize)]
+pub struct TelemetryParameter {
+    #[schema(example = 7.42)]
+    pub value: Option<f64>,
+    #[schema(example = "V")]
+    pub unit: Option<String>,
+    pub valid: bool,
 }
 
 #[derive(ToSchema, Debug, Serialize)]
@@ -15,4 +101,245 @@ pub struct ConfigResponse {
     pub server: crate::config::ServerConfig,
     pub database: crate::config::DatabaseConfig,
     pub message_broker: crate::config::MessageBrokerConfig,
+    pub jobs: crate::config::JobsConfig,
+    pub telemetry: crate::config::TelemetryConfig,
+    pub ingest: crate::config::IngestConfig,
+    pub ground_stations: crate::config::GroundStationsConfig,
+}
+
+/// Connection of the API to the MQTT broker
+#[derive(ToSchema, Debug, Serialize)]
+pub struct BrokerStatusResponse {
+    pub state: ConnectionState,
+    pub since: i64, // unix timestamp the current state was entered at
+}
+
+/// State of the queue between the broker connection and the database writer
+#[derive(ToSchema, Debug, Serialize)]
+pub struct IngestMetricsResponse {
+    pub queue_depth: usize, // messages waiting to be stored
+    pub queue_capacity: usize,
+    pub received: u64, // messages queued since the API started
+    pub dropped: u64,  // messages dropped because the queue was full
+    pub batches: u64,  // batches written
+}
+
+impl From<IngestMetrics> for IngestMetricsResponse {
+    fn from(metrics: IngestMetrics) -> Self {
+        Self {
+            queue_depth: metrics.queue_depth,
+            queue_capacity: metrics.queue_capacity,
+            received: metrics.received,
+            dropped: metrics.dropped,
+            batches: metrics.batches,
+        }
+    }
+}
+
+impl From<ConnectionStatus> for BrokerStatusResponse {
+    fn from(status: ConnectionStatus) -> Self {
+        Self {
+            state: status.state,
+            since: status.since.timestamp(),
+        }
+    }
+}
+
+#[derive(ToSchema, Debug, Serialize)]
+pub struct PassResponse {
+    pub sat_id: i64,
+    pub gs_id: i64,
+    pub aos: i64,           // unix timestamp
+    pub tca: i64,           // unix timestamp
+    pub los: i64,           // unix timestamp
+    pub max_elevation: f64, // degrees
+    pub aos_azimuth: f64,   // degrees
+    pub los_azimuth: f64,   // degrees
+}
+
+#[derive(ToSchema, Debug, Serialize)]
+pub struct JobStatusUpdateResponse {
+    pub timestamp: i64, // unix timestamp
+    pub status: JobStatus,
+    pub accepted: bool,
+    pub rejection_reason: Option<String>,
+}
+
+impl From<JobStatusUpdate> for JobStatusUpdateResponse {
+    fn from(update: JobStatusUpdate) -> Self {
+        Self {
+            timestamp: update.timestamp.timestamp(),
+            status: update.status,
+            accepted: update.rejection_reason.is_none(),
+            rejection_reason: update.rejection_reason,
+        }
+    }
+}
+
+#[derive(ToSchema, Debug, Serialize)]
+pub struct JobStatusResponse {
+    pub job_id: i64,
+    pub status: Option<JobStatus>, // latest accepted status, None if the job was never dispatched
+    pub updated_at: Option<i64>,   // unix timestamp of the latest accepted status
+    pub history: Vec<JobStatusUpdateResponse>,
+}
+
+#[derive(ToSchema, Debug, Serialize)]
+pub struct AlarmResponse {
+    pub id: i64,
+    pub sat_id: i64,
+    pub parameter: String,
+    pub severity: AlarmSeverity,
+    pub value: f64,              // value that raised or last escalated the alarm
+    pub active: bool,            // false once the parameter is back within limits
+    pub raised_at: i64,          // unix timestamp
+    pub cleared_at: Option<i64>, // unix timestamp
+    pub acknowledged_at: Option<i64>,
+    pub acknowledged_by: Option<String>,
+}
+
+impl From<Alarm> for AlarmResponse {
+    fn from(alarm: Alarm) -> Self {
+        Self {
+            id: alarm.id,
+            sat_id: alarm.sat_id,
+            parameter: alarm.parameter,
+            severity: alarm.severity,
+            value: alarm.value,
+            active: alarm.cleared_at.is_none(),
+            raised_at: alarm.raised_at.timestamp(),
+            cleared_at: alarm.cleared_at.map(|t| t.timestamp()),
+            acknowledged_at: alarm.acknowledged_at.map(|t| t.timestamp()),
+            acknowledged_by: alarm.acknowledged_by,
+        }
+    }
+}
+
+/// How the payload of a dead letter is represented
+#[derive(ToSchema, Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PayloadEncoding {
+    Text,
+    Base64,
+}
+
+#[derive(ToSchema, Debug, Serialize)]
+pub struct DeadLetterResponse {
+    pub id: i64,
+    pub topic: String,
+    pub payload: String, // as text if valid UTF-8, base64 otherwise
+    pub payload_encoding: PayloadEncoding, // how payload is represented
+    pub error: String,   // reason of the latest failure
+    pub received_at: i64, // unix timestamp
+    pub replay_attempts: i32,
+    pub replayed_at: Option<i64>, // unix timestamp of the successful replay
+}
+
+impl From<DeadLetter> for DeadLetterResponse {
+    fn from(dead_letter: DeadLetter) -> Self {
+        let (payload, payload_encoding) = match String::from_utf8(dead_letter.payload) {
+            Ok(text) => (text, PayloadEncoding::Text),
+            Err(e) => (BASE64.encode(e.into_bytes()), PayloadEncoding::Base64),
+        };
+
+        Self {
+            id: dead_letter.id,
+            topic: dead_letter.topic,
+            payload,
+            payload_encoding,
+            error: dead_letter.error,
+            received_at: dead_letter.received_at.timestamp(),
+            replay_attempts: dead_letter.replay_attempts,
+            replayed_at: dead_letter.replayed_at.map(|t| t.timestamp()),
+        }
+    }
+}
+
+/// Outcome of replaying pending dead letters
+#[derive(ToSchema, Debug, Serialize)]
+pub struct DeadLetterReplayResponse {
+    pub replayed: usize, // processed successfully
+    pub failed: usize,   // failed again, with their error updated
+}
+
+/// Whether a ground station is sending heartbeats
+#[derive(ToSchema, Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StationStatus {
+    Online,
+    Offline,
+}
+
+#[derive(ToSchema, Debug, Serialize)]
+pub struct GroundStationResponse {
+    pub id: i64,
+    pub name: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: i64,
+    pub tle: Option<String>,
+    pub status: StationStatus,
+    pub last_heartbeat_at: Option<i64>, // unix timestamp the latest heartbeat was received
+}
+
+impl GroundStationResponse {
+    pub fn new(
+        ground_station: GroundStation,
+        status: StationStatus,
+        last_heartbeat_at: Option<i64>,
+    ) -> Self {
+        Self {
+            id: ground_station.id,
+            name: ground_station.name,
+            latitude: ground_station.latitude,
+            longitude: ground_station.longitude,
+            altitude: ground_station.altitude,
+            tle: ground_station.tle,
+            status,
+            last_heartbeat_at,
+        }
+    }
+}
+
+/// A ground station's state as reported in its latest heartbeat
+#[derive(ToSchema, Debug, Serialize)]
+pub struct GroundStationStatusResponse {
+    pub gs_id: i64,
+    pub status: StationStatus,
+    pub last_heartbeat_at: Option<i64>, // unix timestamp the latest heartbeat was received
+    pub sent_at: Option<i64>,           // unix timestamp by the station's clock
+    pub software_version: Option<String>,
+    pub uptime_secs: Option<i64>,
+    pub rotator_status: Option<String>,
+    pub radio_status: Option<String>,
+    pub disk_free_bytes: Option<i64>,
+}
+
+impl GroundStationStatusResponse {
+    pub fn new(gs_id: i64, status: StationStatus, heartbeat: Option<StationHeartbeat>) -> Self {
+        match heartbeat {
+            Some(heartbeat) => Self {
+                gs_id,
+                status,
+                last_heartbeat_at: Some(heartbeat.received_at.timestamp()),
+                sent_at: Some(heartbeat.timestamp.timestamp()),
+                software_version: heartbeat.software_version,
+                uptime_secs: heartbeat.uptime_secs,
+                rotator_status: heartbeat.rotator_status,
+                radio_status: heartbeat.radio_status,
+                disk_free_bytes: heartbeat.disk_free_bytes,
+            },
+            None => Self {
+                gs_id,
+                status,
+                last_heartbeat_at: None,
+                sent_at: None,
+                software_version: None,
+                uptime_secs: None,
+                rotator_status: None,
+                radio_status: None,
+                disk_free_bytes: None,
+            },
+        }
+    }
 }
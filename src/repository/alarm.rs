@@ -0,0 +1,180 @@
+use crate::models::entities::{Alarm, AlarmSeverity};
+use crate::repository::errors::RepositoryError;
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+
+const ALARM_COLUMNS: &str = "id, sat_id, parameter, severity, value, raised_at, cleared_at, \
+                             acknowledged_at, acknowledged_by";
+
+/// Filters of an alarm listing. Unset filters match every alarm.
+pub struct AlarmFilter {
+    pub sat_id: Option<i64>,
+    pub active: Option<bool>,
+    pub acknowledged: Option<bool>,
+    pub limit: i64,
+}
+
+pub struct AlarmRepository {
+    pool: Pool<Postgres>,
+}
+
+impl AlarmRepository {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    /// Alarms matching the filter, most recently raised first
+    pub async fn get_alarms(&self, filter: &AlarmFilter) -> Result<Vec<Alarm>, RepositoryError> {
+        let alarms = sqlx::query_as::<_, Alarm>(&format!(
+            r#"
+            SELECT {ALARM_COLUMNS}
+            FROM alarms
+            WHERE ($1::BIGINT IS NULL OR sat_id = $1)
+              AND ($2::BOOLEAN IS NULL OR (cleared_at IS NULL) = $2)
+              AND ($3::BOOLEAN IS NULL OR (acknowledged_at IS NOT NULL) = $3)
+            ORDER BY raised_at DESC, id DESC
+            LIMIT $4
+            "#
+        ))
+        .bind(filter.sat_id)
+        .bind(filter.active)
+        .bind(filter.acknowledged)
+        .bind(filter.limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(RepositoryError::from)?;
+
+        Ok(alarms)
+    }
+
+    pub async fn get_alarm(&self, id: &i64) -> Result<Option<Alarm>, RepositoryError> {
+        let alarm = sqlx::query_as::<_, Alarm>(&format!(
+            "SELECT {ALARM_COLUMNS} FROM alarms WHERE id = $1"
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::from)?;
+
+        Ok(alarm)
+    }
+
+    /// The active alarm of a satellite parameter, if any
+    pub async fn get_active_alarm(
+        &self,
+        sat_id: i64,
+        parameter: &str,
+    ) -> Result<Option<Alarm>, RepositoryError> {
+        let alarm = sqlx::query_as::<_, Alarm>(&format!(
+            r#"
+            SELECT {ALARM_COLUMNS}
+            FROM alarms
+            WHERE sat_id = $1 AND parameter = $2 AND cleared_at IS NULL
+            "#
+        ))
+        .bind(sat_id)
+        .bind(parameter)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::from)?;
+
+        Ok(alarm)
+    }
+
+    pub async fn raise_alarm(
+        &self,
+        sat_id: i64,
+        parameter: &str,
+        severity: AlarmSeverity,
+        value: f64,
+        raised_at: DateTime<Utc>,
+    ) -> Result<Alarm, RepositoryError> {
+        let alarm = sqlx::query_as::<_, Alarm>(&format!(
+            r#"
+            INSERT INTO alarms (sat_id, parameter, severity, value, raised_at)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING {ALARM_COLUMNS}
+            "#
+        ))
+        .bind(sat_id)
+        .bind(parameter)
+        .bind(severity)
+        .bind(value)
+        .bind(raised_at)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(RepositoryError::from)?;
+
+        Ok(alarm)
+    }
+
+    /// Raise the severity of an active alarm. An escalated alarm needs a new acknowledgement.
+    pub async fn escalate_alarm(
+        &self,
+        id: i64,
+        severity: AlarmSeverity,
+        value: f64,
+    ) -> Result<(), RepositoryError> {
+        sqlx::query(
+            r#"
+            UPDATE alarms
+            SET severity = $2, value = $3, acknowledged_at = NULL, acknowledged_by = NULL
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(severity)
+        .bind(value)
+        .execute(&self.pool)
+        .await
+        .map_err(RepositoryError::from)?;
+
+        Ok(())
+    }
+
+    /// Clear an active alarm. A sample older than the alarm clears it at the time it
+    /// was raised, so an alarm never ends before it starts.
+    pub async fn clear_alarm(
+        &self,
+        id: i64,
+        cleared_at: DateTime<Utc>,
+    ) -> Result<(), RepositoryError> {
+        sqlx::query(
+            r#"
+            UPDATE alarms
+            SET cleared_at = GREATEST(raised_at, $2)
+            WHERE id = $1 AND cleared_at IS NULL
+            "#,
+        )
+        .bind(id)
+        .bind(cleared_at)
+        .execute(&self.pool)
+        .await
+        .map_err(RepositoryError::from)?;
+
+        Ok(())
+    }
+
+    /// Record an operator acknowledgement, returning the updated alarm
+    pub async fn acknowledge_alarm(
+        &self,
+        id: &i64,
+        operator: &str,
+    ) -> Result<Option<Alarm>, RepositoryError> {
+        let alarm = sqlx::query_as::<_, Alarm>(&format!(
+            r#"
+            UPDATE alarms
+            SET acknowledged_at = now(), acknowledged_by = $2
+            WHERE id = $1
+            RETURNING {ALARM_COLUMNS}
+            "#
+        ))
+        .bind(id)
+        .bind(operator)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::from)?;
+
+        Ok(alarm)
+    }
+}
@@ -0,0 +1,153 @@
+use crate::models::entities::DeadLetter;
+use crate::repository::errors::RepositoryError;
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+
+const DEAD_LETTER_COLUMNS: &str =
+    "id, topic, payload, error, received_at, replay_attempts, replayed_at";
+
+/// Filters of a dead letter listing. Unset filters match every dead letter.
+pub struct DeadLetterFilter {
+    pub topic: Option<String>,
+    pub replayed: Option<bool>,
+    pub limit: i64,
+}
+
+pub struct DeadLetterRepository {
+    pool: Pool<Postgres>,
+}
+
+impl DeadLetterRepository {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create_dead_letter(
+        &self,
+        topic: &str,
+        payload: &[u8],
+        error: &str,
+        received_at: DateTime<Utc>,
+    ) -> Result<DeadLetter, RepositoryError> {
+        let dead_letter = sqlx::query_as::<_, DeadLetter>(&format!(
+            r#"
+            INSERT INTO dead_letters (topic, payload, error, received_at)
+            VALUES ($1, $2, $3, $4)
+            RETURNING {DEAD_LETTER_COLUMNS}
+            "#
+        ))
+        .bind(topic)
+        .bind(payload)
+        .bind(error)
+        .bind(received_at)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(RepositoryError::from)?;
+
+        Ok(dead_letter)
+    }
+
+    /// Dead letters matching the filter, most recently received first
+    pub async fn get_dead_letters(
+        &self,
+        filter: &DeadLetterFilter,
+    ) -> Result<Vec<DeadLetter>, RepositoryError> {
+        let dead_letters = sqlx::query_as::<_, DeadLetter>(&format!(
+            r#"
+            SELECT {DEAD_LETTER_COLUMNS}
+            FROM dead_letters
+            WHERE ($1::TEXT IS NULL OR topic = $1)
+              AND ($2::BOOLEAN IS NULL OR (replayed_at IS NOT NULL) = $2)
+            ORDER BY received_at DESC, id DESC
+            LIMIT $3
+            "#
+        ))
+        .bind(&filter.topic)
+        .bind(filter.replayed)
+        .bind(filter.limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(RepositoryError::from)?;
+
+        Ok(dead_letters)
+    }
+
+    /// Dead letters not replayed yet, in the order they were received
+    pub async fn get_pending_dead_letters(
+        &self,
+        topic: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<DeadLetter>, RepositoryError> {
+        let dead_letters = sqlx::query_as::<_, DeadLetter>(&format!(
+            r#"
+            SELECT {DEAD_LETTER_COLUMNS}
+            FROM dead_letters
+            WHERE replayed_at IS NULL AND ($1::TEXT IS NULL OR topic = $1)
+            ORDER BY received_at, id
+            LIMIT $2
+            "#
+        ))
+        .bind(topic)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(RepositoryError::from)?;
+
+        Ok(dead_letters)
+    }
+
+    pub async fn get_dead_letter(&self, id: &i64) -> Result<Option<DeadLetter>, RepositoryError> {
+        let dead_letter = sqlx::query_as::<_, DeadLetter>(&format!(
+            "SELECT {DEAD_LETTER_COLUMNS} FROM dead_letters WHERE id = $1"
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::from)?;
+
+        Ok(dead_letter)
+    }
+
+    /// Claim a pending dead letter for a replay by marking it replayed, so concurrent
+    /// replays cannot both process it. Returns `None` if it does not exist or was
+    /// already replayed or claimed.
+    pub async fn claim_replay(&self, id: &i64) -> Result<Option<DeadLetter>, RepositoryError> {
+        let dead_letter = sqlx::query_as::<_, DeadLetter>(&format!(
+            r#"
+            UPDATE dead_letters
+            SET replayed_at = now(), replay_attempts = replay_attempts + 1
+            WHERE id = $1 AND replayed_at IS NULL
+            RETURNING {DEAD_LETTER_COLUMNS}
+            "#
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::from)?;
+
+        Ok(dead_letter)
+    }
+
+    /// Record a failed replay, replacing the failure reason and releasing the claim
+    pub async fn record_failed_replay(
+        &self,
+        id: &i64,
+        error: &str,
+    ) -> Result<DeadLetter, RepositoryError> {
+        let dead_letter = sqlx::query_as::<_, DeadLetter>(&format!(
+            r#"
+            UPDATE dead_letters
+            SET error = $2, replayed_at = NULL
+            WHERE id = $1
+            RETURNING {DEAD_LETTER_COLUMNS}
+            "#
+        ))
+        .bind(id)
+        .bind(error)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(RepositoryError::from)?;
+
+        Ok(dead_letter)
+    }
+}
@@ -1,6 +1,9 @@
-use crate::models::entities::GroundStation;
+use crate::models::entities::{GroundStation, StationHeartbeat};
 use sqlx::{Pool, Postgres};
 
+const HEARTBEAT_COLUMNS: &str = "gs_id, timestamp, received_at, software_version, uptime_secs, \
+                                 rotator_status, radio_status, disk_free_bytes";
+
 pub struct GroundStationRepository {
     pool: Pool<Postgres>,
 }
@@ -73,4 +76,68 @@ impl GroundStationRepository {
     ) -> Result<Option<()>, Box<dyn std::error::Error + Send + Sync>> {
         Ok(Some(()))
     }
+
+    /// Keep a heartbeat as the station's latest, unless a later one was already received
+    pub async fn save_heartbeat(
+        &self,
+        heartbeat: &StationHeartbeat,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        sqlx::query(
+            r#"
+            INSERT INTO ground_station_heartbeats (
+                gs_id, timestamp, received_at, software_version, uptime_secs,
+                rotator_status, radio_status, disk_free_bytes
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (gs_id) DO UPDATE SET
+                timestamp = EXCLUDED.timestamp,
+                received_at = EXCLUDED.received_at,
+                software_version = EXCLUDED.software_version,
+                uptime_secs = EXCLUDED.uptime_secs,
+                rotator_status = EXCLUDED.rotator_status,
+                radio_status = EXCLUDED.radio_status,
+                disk_free_bytes = EXCLUDED.disk_free_bytes
+            WHERE ground_station_heartbeats.received_at <= EXCLUDED.received_at
+            "#,
+        )
+        .bind(heartbeat.gs_id)
+        .bind(heartbeat.timestamp)
+        .bind(heartbeat.received_at)
+        .bind(&heartbeat.software_version)
+        .bind(heartbeat.uptime_secs)
+        .bind(&heartbeat.rotator_status)
+        .bind(&heartbeat.radio_status)
+        .bind(heartbeat.disk_free_bytes)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_heartbeat(
+        &self,
+        gs_id: &i64,
+    ) -> Result<Option<StationHeartbeat>, Box<dyn std::error::Error + Send + Sync>> {
+        let heartbeat = sqlx::query_as::<_, StationHeartbeat>(&format!(
+            "SELECT {HEARTBEAT_COLUMNS} FROM ground_station_heartbeats WHERE gs_id = $1"
+        ))
+        .bind(gs_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(heartbeat)
+    }
+
+    /// Latest heartbeat of every station that sent one
+    pub async fn get_heartbeats(
+        &self,
+    ) -> Result<Vec<StationHeartbeat>, Box<dyn std::error::Error + Send + Sync>> {
+        let heartbeats = sqlx::query_as::<_, StationHeartbeat>(&format!(
+            "SELECT {HEARTBEAT_COLUMNS} FROM ground_station_heartbeats"
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(heartbeats)
+    }
 }
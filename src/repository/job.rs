@@ -2,6 +2,29 @@ use crate::models::entities::Job;
 use chrono::{DateTime, Utc};
 use sqlx::{Pool, Postgres};
 
+#[derive(sqlx::FromRow)]
+struct JobDb {
+    id: i64,
+    sat_id: i64,
+    gs_id: i64,
+    start: DateTime<Utc>,
+    end: Option<DateTime<Utc>>,
+    commands: Vec<String>,
+}
+
+impl From<JobDb> for Job {
+    fn from(row: JobDb) -> Self {
+        Self {
+            id: row.id,
+            gs_id: row.gs_id,
+            sat_id: row.sat_id,
+            start_time: row.start.timestamp(),
+            end_time: row.end.unwrap_or(row.start).timestamp(),
+            commands: row.commands,
+        }
+    }
+}
+
 pub struct JobRepository {
     pool: Pool<Postgres>,
 }
@@ -11,31 +34,116 @@ impl JobRepository {
         Self { pool }
     }
 
+    /// Insert a job together with its commands and return the generated job ID
     pub async fn create_job(
         &self,
         job: &Job,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // Convert timestamps from i32 to DateTime
+    ) -> Result<i64, Box<dyn std::error::Error + Send + Sync>> {
+        // Convert unix timestamps to DateTime
         let start_dt =
-            DateTime::from_timestamp(job.start_time as i64, 0).unwrap_or_else(|| Utc::now());
-        let end_dt = DateTime::from_timestamp(job.end_time as i64, 0).unwrap_or_else(|| Utc::now());
+            DateTime::from_timestamp(job.start_time, 0).ok_or("Invalid job start time")?;
+        let end_dt = DateTime::from_timestamp(job.end_time, 0).ok_or("Invalid job end time")?;
+
+        let mut tx = self.pool.begin().await?;
+
+        let id: i64 = sqlx::query_scalar(
+            r#"
+            INSERT INTO jobs (sat_id, gs_id, start, "end")
+            VALUES ($1, $2, $3, $4)
+            RETURNING id
+            "#,
+        )
+        .bind(job.sat_id)
+        .bind(job.gs_id)
+        .bind(start_dt)
+        .bind(end_dt)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        for (position, command) in job.commands.iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO job_commands (job_id, position, command)
+                VALUES ($1, $2, $3)
+                "#,
+            )
+            .bind(id)
+            .bind(position as i32)
+            .bind(command)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(id)
+    }
 
-        // Note: DB schema doesn't have 'commands' column
-        // Commands are stored elsewhere or not persisted
-        sqlx::query!(
+    /// Fetch one page of jobs ordered by `(start, id)`, most recent first, starting
+    /// after the cursor
+    pub async fn get_jobs_page(
+        &self,
+        after: Option<(DateTime<Utc>, i64)>,
+        limit: i64,
+    ) -> Result<Vec<Job>, Box<dyn std::error::Error + Send + Sync>> {
+        let rows = sqlx::query_as::<_, JobDb>(
             r#"
-            INSERT INTO jobs (id, sat_id, gs_id, start, "end")
-            VALUES ($1, $2, $3, $4, $5)
+            SELECT j.id, j.sat_id, j.gs_id, j.start, j."end",
+                   COALESCE(
+                       array_agg(c.command ORDER BY c.position) FILTER (WHERE c.command IS NOT NULL),
+                       '{}'
+                   ) AS commands
+            FROM jobs j
+            LEFT JOIN job_commands c ON c.job_id = j.id
+            WHERE $1::TIMESTAMPTZ IS NULL OR (j.start, j.id) < ($1, $2::BIGINT)
+            GROUP BY j.id
+            ORDER BY j.start DESC, j.id DESC
+            LIMIT $3
             "#,
-            job.id,
-            job.sat_id,
-            job.gs_id,
-            start_dt,
-            end_dt
         )
-        .execute(&self.pool)
+        .bind(after.map(|(start, _)| start))
+        .bind(after.map(|(_, id)| id))
+        .bind(limit)
+        .fetch_all(&self.pool)
         .await?;
 
+        Ok(rows.into_iter().map(Job::from).collect())
+    }
+
+    pub async fn get_job(
+        &self,
+        id: &i64,
+    ) -> Result<Option<Job>, Box<dyn std::error::Error + Send + Sync>> {
+        let row = sqlx::query_as::<_, JobDb>(
+            r#"
+            SELECT j.id, j.sat_id, j.gs_id, j.start, j."end",
+                   COALESCE(
+                       array_agg(c.command ORDER BY c.position) FILTER (WHERE c.command IS NOT NULL),
+                       '{}'
+                   ) AS commands
+            FROM jobs j
+            LEFT JOIN job_commands c ON c.job_id = j.id
+            WHERE j.id = $1
+            GROUP BY j.id
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(Job::from))
+    }
+
+    /// Delete a job, with its commands and status history
+    pub async fn delete_job(
+        &self,
+        id: &i64,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        sqlx::query("DELETE FROM jobs WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
         Ok(())
     }
 }
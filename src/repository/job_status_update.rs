@@ -1,6 +1,14 @@
 use crate::models::entities::{JobStatus, JobStatusUpdate};
 use sqlx::{Pool, Postgres};
 
+/// What became of a status update checked against the job's current status
+pub enum StatusOutcome {
+    JobNotFound,
+    Accepted,
+    /// Appended to the history with the reason it was rejected
+    Rejected(String),
+}
+
 pub struct JobStatusUpdateRepository {
     pool: Pool<Postgres>,
 }
@@ -10,72 +18,108 @@ impl JobStatusUpdateRepository {
         Self { pool }
     }
 
-    pub async fn create_status_update(
+    /// Append an update to the job's status history, rejected with a reason if it was
+    /// reported by another station than the job's, or if `check` does not allow moving
+    /// from the job's latest accepted status to the update's.
+    /// The job is locked while checking, so concurrent updates of a job are checked
+    /// one after the other. Updates are never modified afterwards.
+    pub async fn record_status_update<F>(
         &self,
         update: &JobStatusUpdate,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        sqlx::query!(
+        check: F,
+    ) -> Result<StatusOutcome, Box<dyn std::error::Error + Send + Sync>>
+    where
+        F: FnOnce(Option<JobStatus>, JobStatus) -> Result<(), String>,
+    {
+        let mut tx = self.pool.begin().await?;
+
+        let job_gs_id: Option<i64> =
+            sqlx::query_scalar("SELECT gs_id FROM jobs WHERE id = $1 FOR UPDATE")
+                .bind(update.job_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+        let Some(job_gs_id) = job_gs_id else {
+            return Ok(StatusOutcome::JobNotFound);
+        };
+
+        let current: Option<JobStatus> = sqlx::query_scalar(
             r#"
-            INSERT INTO jobs_status_updates (job_id, timestamp, status)
-            VALUES ($1, $2, $3)
+            SELECT status
+            FROM jobs_status_updates
+            WHERE job_id = $1 AND rejection_reason IS NULL
+            ORDER BY id DESC
+            LIMIT 1
             "#,
-            update.job_id,
-            update.timestamp,
-            update.status as JobStatus
         )
-        .execute(&self.pool)
+        .bind(update.job_id)
+        .fetch_optional(&mut *tx)
         .await?;
+        let rejection_reason = match update.gs_id {
+            Some(gs_id) if gs_id != job_gs_id => Some(format!(
+                "reported by ground station {}, but the job belongs to ground station {}",
+                gs_id, job_gs_id
+            )),
+            _ => check(current, update.status).err(),
+        };
 
-        Ok(())
+        sqlx::query(
+            r#"
+            INSERT INTO jobs_status_updates (job_id, timestamp, status, rejection_reason)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(update.job_id)
+        .bind(update.timestamp)
+        .bind(update.status)
+        .bind(&rejection_reason)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(match rejection_reason {
+            Some(reason) => StatusOutcome::Rejected(reason),
+            None => StatusOutcome::Accepted,
+        })
     }
 
+    /// Full status history of a job, accepted and rejected, in arrival order
     pub async fn get_job_status_updates(
         &self,
         job_id: &i64,
     ) -> Result<Vec<JobStatusUpdate>, Box<dyn std::error::Error + Send + Sync>> {
-        let updates = sqlx::query_as!(
-            JobStatusUpdate,
+        let updates = sqlx::query_as::<_, JobStatusUpdate>(
             r#"
-            SELECT job_id, timestamp, status as "status: JobStatus"
+            SELECT job_id, timestamp, status, rejection_reason
             FROM jobs_status_updates
             WHERE job_id = $1
-            ORDER BY timestamp ASC
+            ORDER BY id ASC
             "#,
-            job_id
         )
+        .bind(job_id)
         .fetch_all(&self.pool)
         .await?;
 
         Ok(updates)
     }
 
+    /// Most recent accepted status of a job
     pub async fn get_latest_status(
         &self,
         job_id: &i64,
     ) -> Result<Option<JobStatusUpdate>, Box<dyn std::error::Error + Send + Sync>> {
-        let query_result = sqlx::query_as!(
-            JobStatusUpdate,
+        let update = sqlx::query_as::<_, JobStatusUpdate>(
             r#"
-            SELECT job_id, timestamp, status as "status: JobStatus"
+            SELECT job_id, timestamp, status, rejection_reason
             FROM jobs_status_updates
-            WHERE job_id = $1
-            ORDER BY timestamp DESC
+            WHERE job_id = $1 AND rejection_reason IS NULL
+            ORDER BY id DESC
             LIMIT 1
             "#,
-            job_id
         )
-        .fetch_one(&self.pool)
-        .await;
+        .bind(job_id)
+        .fetch_optional(&self.pool)
+        .await?;
 
-        match query_result {
-            Ok(update) => Ok(Some(update)),
-            Err(e) => {
-                if let sqlx::Error::RowNotFound = e {
-                    Ok(None)
-                } else {
-                    Err(e.into())
-                }
-            }
-        }
+        Ok(update)
     }
 }
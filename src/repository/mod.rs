@@ -1,6 +1,10 @@
+pub mod alarm;
+pub mod dead_letter;
 pub mod errors;
 pub mod ground_station;
 pub mod job;
 pub mod job_status_update;
 pub mod satellite;
 pub mod telemetry;
+pub mod telemetry_decoder;
+pub mod telemetry_limit;
@@ -1,4 +1,4 @@
-use crate::models::entities::Satellite;
+use crate::models::entities::{CcsdsConfig, Satellite};
 use crate::repository::errors::RepositoryError;
 use sqlx::{Pool, Postgres};
 
@@ -69,6 +69,26 @@ impl SatelliteRepository {
         Ok(satellite)
     }
 
+    /// Fetch a single satellite by name (case-insensitive)
+    pub async fn get_satellite_by_name(
+        &self,
+        name: &str,
+    ) -> Result<Option<Satellite>, RepositoryError> {
+        let satellite = sqlx::query_as::<_, Satellite>(
+            r#"
+            SELECT id, name, tle, downlink_frequency, uplink_frequency
+            FROM satellites
+            WHERE LOWER(name) = LOWER($1)
+            "#,
+        )
+        .bind(name)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::from)?;
+
+        Ok(satellite)
+    }
+
     /// Update only the TLE of a satellite
     pub async fn update_tle(&self, id: &i64, tle: &str) -> Result<bool, RepositoryError> {
         let result = sqlx::query!(
@@ -87,6 +107,46 @@ impl SatelliteRepository {
         Ok(result.rows_affected() > 0)
     }
 
+    /// Fetch the CCSDS framing settings of a satellite, if it uses CCSDS framing
+    pub async fn get_ccsds_config(&self, id: &i64) -> Result<Option<CcsdsConfig>, RepositoryError> {
+        let config = sqlx::query_as::<_, CcsdsConfig>(
+            r#"
+            SELECT ccsds_spacecraft_id AS spacecraft_id, ccsds_fecf AS fecf
+            FROM satellites
+            WHERE id = $1 AND ccsds_spacecraft_id IS NOT NULL
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::from)?;
+
+        Ok(config)
+    }
+
+    /// Set the CCSDS framing settings of a satellite
+    pub async fn set_ccsds_config(
+        &self,
+        id: &i64,
+        config: &CcsdsConfig,
+    ) -> Result<bool, RepositoryError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE satellites
+            SET ccsds_spacecraft_id = $2, ccsds_fecf = $3
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(config.spacecraft_id)
+        .bind(config.fecf)
+        .execute(&self.pool)
+        .await
+        .map_err(RepositoryError::from)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
     /// Delete a satellite by ID
     pub async fn delete_satellite(&self, id: &i64) -> Result<bool, RepositoryError> {
         let result = sqlx::query!(
@@ -1,14 +1,60 @@
-use chrono::{DateTime, Utc};
-use rustar_types::telemetry::TelemetryRecord;
+use crate::models::entities::{TelemetryFrame, TelemetryReception};
+use chrono::{DateTime, Duration, Utc};
+use futures::stream::BoxStream;
 use sqlx::{Pool, Postgres};
+use std::collections::HashMap;
+use std::sync::OnceLock;
 
+/// Columns of a `TelemetryDb`, including every station that received the frame
+const TELEMETRY_COLUMNS: &str = "id, timestamp, sat_id, gs_id, payload, \
+    source_callsign, destination_callsign, apid, sequence_count, \
+    ARRAY(SELECT r.gs_id FROM telemetry_receptions r \
+          WHERE r.telemetry_id = telemetry.id ORDER BY r.received_at) AS gs_ids";
+
+/// Matches frames received by the station bound as `$2`, not only the first one
+const RECEIVED_BY: &str = "EXISTS (SELECT 1 FROM telemetry_receptions r \
+    WHERE r.telemetry_id = telemetry.id AND r.gs_id = $2)";
+
+/// Row of the `telemetry` table. The payload holds the frame exactly as received.
 #[derive(sqlx::FromRow)]
-struct TelemetryDb {
-    id: i64,
-    timestamp: DateTime<Utc>,
-    sat_id: i64,
-    gs_id: i64,
-    payload: Vec<u8>,
+pub struct TelemetryDb {
+    pub id: i64,
+    pub timestamp: DateTime<Utc>,
+    pub sat_id: i64,
+    pub gs_id: i64,
+    pub payload: Option<Vec<u8>>,
+    pub source_callsign: Option<String>,
+    pub destination_callsign: Option<String>,
+    pub apid: Option<i32>,
+    pub sequence_count: Option<i32>,
+    pub gs_ids: Vec<i64>,
+}
+
+/// A received frame to store, with the hash identifying it whichever station received it
+pub struct FrameToSave<'a> {
+    pub frame: &'a TelemetryFrame,
+    pub frame_hash: &'a [u8],
+    pub reception: &'a TelemetryReception,
+}
+
+/// Result of storing a received frame
+pub enum SaveOutcome {
+    /// The frame was new and stored under this telemetry ID
+    Stored(i64),
+    /// The frame was already stored under this telemetry ID; only the reception was added
+    Duplicate(i64),
+}
+
+/// Filters and keyset position of a telemetry page
+pub struct TelemetryPageQuery {
+    pub sat_id: i64,
+    pub gs_id: Option<i64>,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// `(timestamp, id)` of the last row of the previous page
+    pub after: Option<(DateTime<Utc>, i64)>,
+    pub ascending: bool,
+    pub limit: i64,
 }
 
 pub struct TelemetryRepository {
@@ -22,75 +68,348 @@ impl TelemetryRepository {
 
     pub async fn get_latest(
         &self,
-        _sat_name: String,
+        sat_id: i64,
         limit: i32,
-    ) -> Result<Vec<TelemetryRecord>, Box<dyn std::error::Error + Send + Sync>> {
-        // Note: This needs to be adapted based on TelemetryRecord structure
-        // The database stores raw payload bytes, but TelemetryRecord expects decoded fields
-        // TODO: Implement proper payload decoding
-        let _records = sqlx::query_as::<_, TelemetryDb>(
+    ) -> Result<Vec<TelemetryDb>, Box<dyn std::error::Error + Send + Sync>> {
+        let records = sqlx::query_as::<_, TelemetryDb>(&format!(
             r#"
-            SELECT id, timestamp, sat_id, gs_id, payload
+            SELECT {TELEMETRY_COLUMNS}
             FROM telemetry
+            WHERE sat_id = $1
             ORDER BY timestamp DESC
-            LIMIT $1
-            "#,
-        )
+            LIMIT $2
+            "#
+        ))
+        .bind(sat_id)
         .bind(limit as i64)
         .fetch_all(&self.pool)
         .await?;
 
-        // For now, return empty vec since we need payload decoding logic
-        // TODO: Decode payload bytes into temperature, voltage, current, battery_level
-        Ok(vec![])
+        Ok(records)
     }
 
     pub async fn get_historic(
         &self,
-        _sat_name: String,
-        start_time: Option<i64>,
-        end_time: Option<i64>,
-    ) -> Result<Vec<TelemetryRecord>, Box<dyn std::error::Error + Send + Sync>> {
-        let start_ts = start_time
-            .map(|ts| DateTime::from_timestamp(ts, 0).unwrap_or_default())
-            .unwrap_or(DateTime::UNIX_EPOCH);
-        let end_ts = end_time
-            .map(|ts| DateTime::from_timestamp(ts, 0).unwrap_or_default())
-            .unwrap_or_else(|| Utc::now());
-
-        let _records = sqlx::query_as::<_, TelemetryDb>(
+        sat_id: i64,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<TelemetryDb>, Box<dyn std::error::Error + Send + Sync>> {
+        let records = sqlx::query_as::<_, TelemetryDb>(&format!(
             r#"
-            SELECT id, timestamp, sat_id, gs_id, payload
+            SELECT {TELEMETRY_COLUMNS}
             FROM telemetry
-            WHERE timestamp >= $1 AND timestamp <= $2
+            WHERE sat_id = $1 AND timestamp >= $2 AND timestamp <= $3
             ORDER BY timestamp DESC
+            "#
+        ))
+        .bind(sat_id)
+        .bind(start)
+        .bind(end)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records)
+    }
+
+    /// Fetch one page of telemetry ordered by `(timestamp, id)`, starting after the cursor
+    pub async fn get_page(
+        &self,
+        query: &TelemetryPageQuery,
+    ) -> Result<Vec<TelemetryDb>, Box<dyn std::error::Error + Send + Sync>> {
+        let (cmp, dir) = if query.ascending {
+            (">", "ASC")
+        } else {
+            ("<", "DESC")
+        };
+        let sql = format!(
+            r#"
+            SELECT {TELEMETRY_COLUMNS}
+            FROM telemetry
+            WHERE sat_id = $1
+              AND ($2::BIGINT IS NULL OR {RECEIVED_BY})
+              AND timestamp >= $3 AND timestamp <= $4
+              AND ($5::TIMESTAMPTZ IS NULL OR (timestamp, id) {cmp} ($5, $6::BIGINT))
+            ORDER BY timestamp {dir}, id {dir}
+            LIMIT $7
+            "#
+        );
+
+        let records = sqlx::query_as::<_, TelemetryDb>(&sql)
+            .bind(query.sat_id)
+            .bind(query.gs_id)
+            .bind(query.start)
+            .bind(query.end)
+            .bind(query.after.map(|(ts, _)| ts))
+            .bind(query.after.map(|(_, id)| id))
+            .bind(query.limit)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(records)
+    }
+
+    /// Stream a satellite's telemetry in a time range, oldest first, without loading
+    /// the whole range into memory
+    pub fn stream_range(
+        &self,
+        sat_id: i64,
+        gs_id: Option<i64>,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> BoxStream<'_, Result<TelemetryDb, sqlx::Error>> {
+        // The query text must outlive the stream, so it is built once
+        static SQL: OnceLock<String> = OnceLock::new();
+        let sql = SQL.get_or_init(|| {
+            format!(
+                r#"
+                SELECT {TELEMETRY_COLUMNS}
+                FROM telemetry
+                WHERE sat_id = $1
+                  AND ($2::BIGINT IS NULL OR {RECEIVED_BY})
+                  AND timestamp >= $3 AND timestamp <= $4
+                ORDER BY timestamp ASC, id ASC
+                "#
+            )
+        });
+
+        sqlx::query_as::<_, TelemetryDb>(sql)
+            .bind(sat_id)
+            .bind(gs_id)
+            .bind(start)
+            .bind(end)
+            .fetch(&self.pool)
+    }
+
+    /// Store received frames in one transaction. A frame is not stored again when a
+    /// frame of the same satellite with the same hash and a timestamp within `tolerance`
+    /// is already stored or comes earlier in the batch. Either way its reception is
+    /// recorded against the stored frame. Outcomes are returned in the order of `frames`.
+    pub async fn save_batch(
+        &self,
+        frames: &[FrameToSave<'_>],
+        tolerance: Duration,
+    ) -> Result<Vec<SaveOutcome>, Box<dyn std::error::Error + Send + Sync>> {
+        if frames.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut tx = self.pool.begin().await?;
+
+        // Serializes concurrent saves of the same frame between the lookup and the
+        // insert, which no row lock covers while the frame is not stored yet. Locks are
+        // taken in key order so concurrent batches cannot deadlock.
+        sqlx::query(
+            r#"
+            SELECT pg_advisory_xact_lock(k.key)
+            FROM (
+                SELECT DISTINCT hashtext(f.sat_id::TEXT || ':' || encode(f.frame_hash, 'hex')) AS key
+                FROM UNNEST($1::BIGINT[], $2::BYTEA[]) AS f(sat_id, frame_hash)
+                ORDER BY key
+            ) k
             "#,
         )
-        .bind(start_ts)
-        .bind(end_ts)
-        .fetch_all(&self.pool)
+        .bind(frames.iter().map(|f| f.frame.sat_id).collect::<Vec<_>>())
+        .bind(frames.iter().map(|f| f.frame_hash).collect::<Vec<_>>())
+        .execute(&mut *tx)
+        .await?;
+
+        let existing: Vec<(i64, i64)> = sqlx::query_as(
+            r#"
+            SELECT DISTINCT ON (f.idx) f.idx, t.id
+            FROM UNNEST($1::BIGINT[], $2::BYTEA[], $3::TIMESTAMPTZ[], $4::TIMESTAMPTZ[])
+                WITH ORDINALITY AS f(sat_id, frame_hash, earliest, latest, idx)
+            JOIN telemetry t
+              ON t.sat_id = f.sat_id AND t.frame_hash = f.frame_hash
+             AND t.timestamp BETWEEN f.earliest AND f.latest
+            ORDER BY f.idx, t.id
+            "#,
+        )
+        .bind(frames.iter().map(|f| f.frame.sat_id).collect::<Vec<_>>())
+        .bind(frames.iter().map(|f| f.frame_hash).collect::<Vec<_>>())
+        .bind(
+            frames
+                .iter()
+                .map(|f| f.frame.timestamp - tolerance)
+                .collect::<Vec<_>>(),
+        )
+        .bind(
+            frames
+                .iter()
+                .map(|f| f.frame.timestamp + tolerance)
+                .collect::<Vec<_>>(),
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        enum Slot {
+            Existing(i64),
+            /// Repeats the frame at this index of the batch
+            Repeats(usize),
+            New,
+        }
+        let mut slots: Vec<Option<Slot>> = (0..frames.len()).map(|_| None).collect();
+        for (idx, id) in existing {
+            slots[idx as usize - 1] = Some(Slot::Existing(id));
+        }
+        let mut new: Vec<usize> = Vec::new();
+        for (i, frame) in frames.iter().enumerate() {
+            if slots[i].is_some() {
+                continue;
+            }
+            let repeated = new.iter().copied().find(|&j| {
+                let other = &frames[j];
+                other.frame.sat_id == frame.frame.sat_id
+                    && other.frame_hash == frame.frame_hash
+                    && (other.frame.timestamp - frame.frame.timestamp).abs() <= tolerance
+            });
+            slots[i] = Some(match repeated {
+                Some(j) => Slot::Repeats(j),
+                None => {
+                    new.push(i);
+                    Slot::New
+                }
+            });
+        }
+
+        // IDs are reserved up front, so every frame's outcome is known before inserting
+        let ids: Vec<i64> = sqlx::query_scalar(
+            "SELECT nextval(pg_get_serial_sequence('telemetry', 'id')) FROM generate_series(1, $1)",
+        )
+        .bind(new.len() as i64)
+        .fetch_all(&mut *tx)
+        .await?;
+        let new_ids: HashMap<usize, i64> = new.iter().copied().zip(ids).collect();
+
+        if !new.is_empty() {
+            let new_frames: Vec<&FrameToSave> = new.iter().map(|&i| &frames[i]).collect();
+            sqlx::query(
+                r#"
+                INSERT INTO telemetry (
+                    id, timestamp, sat_id, gs_id, payload, frame_hash,
+                    source_callsign, destination_callsign, apid, sequence_count
+                )
+                SELECT * FROM UNNEST(
+                    $1::BIGINT[], $2::TIMESTAMPTZ[], $3::BIGINT[], $4::BIGINT[], $5::BYTEA[],
+                    $6::BYTEA[], $7::TEXT[], $8::TEXT[], $9::INTEGER[], $10::INTEGER[]
+                )
+                "#,
+            )
+            .bind(new.iter().map(|i| new_ids[i]).collect::<Vec<_>>())
+            .bind(
+                new_frames
+                    .iter()
+                    .map(|f| f.frame.timestamp)
+                    .collect::<Vec<_>>(),
+            )
+            .bind(
+                new_frames
+                    .iter()
+                    .map(|f| f.frame.sat_id)
+                    .collect::<Vec<_>>(),
+            )
+            .bind(
+                new_frames
+                    .iter()
+                    .map(|f| f.reception.gs_id)
+                    .collect::<Vec<_>>(),
+            )
+            .bind(
+                new_frames
+                    .iter()
+                    .map(|f| f.frame.payload.as_slice())
+                    .collect::<Vec<_>>(),
+            )
+            .bind(new_frames.iter().map(|f| f.frame_hash).collect::<Vec<_>>())
+            .bind(
+                new_frames
+                    .iter()
+                    .map(|f| f.frame.source_callsign.as_deref())
+                    .collect::<Vec<_>>(),
+            )
+            .bind(
+                new_frames
+                    .iter()
+                    .map(|f| f.frame.destination_callsign.as_deref())
+                    .collect::<Vec<_>>(),
+            )
+            .bind(new_frames.iter().map(|f| f.frame.apid).collect::<Vec<_>>())
+            .bind(
+                new_frames
+                    .iter()
+                    .map(|f| f.frame.sequence_count)
+                    .collect::<Vec<_>>(),
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let outcomes: Vec<SaveOutcome> = slots
+            .into_iter()
+            .enumerate()
+            .map(|(i, slot)| match slot {
+                Some(Slot::Existing(id)) => SaveOutcome::Duplicate(id),
+                Some(Slot::Repeats(j)) => SaveOutcome::Duplicate(new_ids[&j]),
+                _ => SaveOutcome::Stored(new_ids[&i]),
+            })
+            .collect();
+        let telemetry_ids: Vec<i64> = outcomes
+            .iter()
+            .map(|outcome| match outcome {
+                SaveOutcome::Stored(id) | SaveOutcome::Duplicate(id) => *id,
+            })
+            .collect();
+
+        // A station repeating a frame keeps its first reception
+        sqlx::query(
+            r#"
+            INSERT INTO telemetry_receptions
+                (telemetry_id, gs_id, received_at, rssi, snr, frequency_offset)
+            SELECT * FROM UNNEST(
+                $1::BIGINT[], $2::BIGINT[], $3::TIMESTAMPTZ[],
+                $4::DOUBLE PRECISION[], $5::DOUBLE PRECISION[], $6::DOUBLE PRECISION[]
+            )
+            ON CONFLICT (telemetry_id, gs_id) DO NOTHING
+            "#,
+        )
+        .bind(telemetry_ids)
+        .bind(frames.iter().map(|f| f.reception.gs_id).collect::<Vec<_>>())
+        .bind(
+            frames
+                .iter()
+                .map(|f| f.reception.received_at)
+                .collect::<Vec<_>>(),
+        )
+        .bind(frames.iter().map(|f| f.reception.rssi).collect::<Vec<_>>())
+        .bind(frames.iter().map(|f| f.reception.snr).collect::<Vec<_>>())
+        .bind(
+            frames
+                .iter()
+                .map(|f| f.reception.frequency_offset)
+                .collect::<Vec<_>>(),
+        )
+        .execute(&mut *tx)
         .await?;
 
-        // For now, return empty vec since we need payload decoding logic
-        // TODO: Decode payload bytes into temperature, voltage, current, battery_level
-        Ok(vec![])
+        tx.commit().await?;
+        Ok(outcomes)
     }
 
-    pub async fn save(
+    /// Every reception of a stored frame, earliest first
+    pub async fn get_receptions(
         &self,
-        _telemetry: TelemetryRecord,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // TODO: This method needs to be completely rewritten to match the actual DB schema
-        // The DB uses: (id: i64, timestamp: DateTime<Utc>, sat_id: i64, gs_id: i64, payload: bytes)
-        // But TelemetryRecord (from rustar-types) uses different fields
-        //
-        // Required steps:
-        // 1. Encode TelemetryRecord fields into payload bytes
-        // 2. Get sat_id and gs_id from context
-        // 3. Convert timestamp to DateTime<Utc>
-        // 4. Parse/convert id to i64
-
-        // For now, this is a no-op stub
-        Ok(())
+        telemetry_id: &i64,
+    ) -> Result<Vec<TelemetryReception>, Box<dyn std::error::Error + Send + Sync>> {
+        let receptions = sqlx::query_as::<_, TelemetryReception>(
+            r#"
+            SELECT gs_id, received_at, rssi, snr, frequency_offset
+            FROM telemetry_receptions
+            WHERE telemetry_id = $1
+            ORDER BY received_at
+            "#,
+        )
+        .bind(telemetry_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(receptions)
     }
 }
@@ -0,0 +1,85 @@
+use crate::models::entities::TelemetryDecoder;
+use crate::repository::errors::RepositoryError;
+use sqlx::{types::Json, Pool, Postgres};
+
+pub struct TelemetryDecoderRepository {
+    pool: Pool<Postgres>,
+}
+
+impl TelemetryDecoderRepository {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    /// Fetch the decoder of a satellite's APID, or its default decoder when `apid`
+    /// is `None`, if one was configured
+    pub async fn get_decoder(
+        &self,
+        sat_id: &i64,
+        apid: Option<i32>,
+    ) -> Result<Option<TelemetryDecoder>, RepositoryError> {
+        let definition: Option<Json<TelemetryDecoder>> = sqlx::query_scalar(
+            r#"
+            SELECT definition
+            FROM telemetry_decoders
+            WHERE sat_id = $1 AND apid IS NOT DISTINCT FROM $2
+            "#,
+        )
+        .bind(sat_id)
+        .bind(apid)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::from)?;
+
+        Ok(definition.map(|Json(decoder)| decoder))
+    }
+
+    /// Fetch every decoder of a satellite with the APID it is attached to
+    pub async fn get_decoders(
+        &self,
+        sat_id: &i64,
+    ) -> Result<Vec<(Option<i32>, TelemetryDecoder)>, RepositoryError> {
+        let rows: Vec<(Option<i32>, Json<TelemetryDecoder>)> = sqlx::query_as(
+            r#"
+            SELECT apid, definition
+            FROM telemetry_decoders
+            WHERE sat_id = $1
+            "#,
+        )
+        .bind(sat_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(RepositoryError::from)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(apid, Json(decoder))| (apid, decoder))
+            .collect())
+    }
+
+    /// Create or replace the decoder of a satellite's APID, or its default decoder
+    /// when `apid` is `None`
+    pub async fn set_decoder(
+        &self,
+        sat_id: &i64,
+        apid: Option<i32>,
+        decoder: &TelemetryDecoder,
+    ) -> Result<(), RepositoryError> {
+        sqlx::query(
+            r#"
+            INSERT INTO telemetry_decoders (sat_id, apid, definition, updated_at)
+            VALUES ($1, $2, $3, now())
+            ON CONFLICT (sat_id, apid)
+            DO UPDATE SET definition = EXCLUDED.definition, updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(sat_id)
+        .bind(apid)
+        .bind(Json(decoder))
+        .execute(&self.pool)
+        .await
+        .map_err(RepositoryError::from)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+use crate::models::entities::TelemetryLimit;
+use crate::repository::errors::RepositoryError;
+use sqlx::{Pool, Postgres};
+
+pub struct TelemetryLimitRepository {
+    pool: Pool<Postgres>,
+}
+
+impl TelemetryLimitRepository {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    /// Limits configured for the parameters of a satellite
+    pub async fn get_limits(&self, sat_id: &i64) -> Result<Vec<TelemetryLimit>, RepositoryError> {
+        let limits = sqlx::query_as::<_, TelemetryLimit>(
+            r#"
+            SELECT parameter, red_low, yellow_low, yellow_high, red_high, persistence
+            FROM telemetry_limits
+            WHERE sat_id = $1
+            ORDER BY parameter
+            "#,
+        )
+        .bind(sat_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(RepositoryError::from)?;
+
+        Ok(limits)
+    }
+
+    /// Replace every limit of a satellite. Active alarms of parameters that no longer
+    /// have limits are cleared with them, and their number is returned.
+    pub async fn set_limits(
+        &self,
+        sat_id: &i64,
+        limits: &[TelemetryLimit],
+    ) -> Result<u64, RepositoryError> {
+        let mut tx = self.pool.begin().await.map_err(RepositoryError::from)?;
+
+        sqlx::query("DELETE FROM telemetry_limits WHERE sat_id = $1")
+            .bind(sat_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(RepositoryError::from)?;
+
+        for limit in limits {
+            sqlx::query(
+                r#"
+                INSERT INTO telemetry_limits
+                    (sat_id, parameter, red_low, yellow_low, yellow_high, red_high, persistence)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                "#,
+            )
+            .bind(sat_id)
+            .bind(&limit.parameter)
+            .bind(limit.red_low)
+            .bind(limit.yellow_low)
+            .bind(limit.yellow_high)
+            .bind(limit.red_high)
+            .bind(limit.persistence)
+            .execute(&mut *tx)
+            .await
+            .map_err(RepositoryError::from)?;
+        }
+
+        let parameters: Vec<&str> = limits.iter().map(|l| l.parameter.as_str()).collect();
+        let cleared = sqlx::query(
+            r#"
+            UPDATE alarms
+            SET cleared_at = GREATEST(raised_at, now())
+            WHERE sat_id = $1 AND cleared_at IS NULL AND parameter <> ALL($2)
+            "#,
+        )
+        .bind(sat_id)
+        .bind(&parameters)
+        .execute(&mut *tx)
+        .await
+        .map_err(RepositoryError::from)?
+        .rows_affected();
+
+        tx.commit().await.map_err(RepositoryError::from)?;
+        Ok(cleared)
+    }
+}
@@ -0,0 +1,143 @@
+use crate::models::entities::TelemetryLimit;
+use crate::models::requests::{AlarmAcknowledgeRequest, AlarmListRequest};
+use crate::services::{alarm_service::AlarmService, errors::ServiceError};
+use actix_web::{get, post, put, web, HttpResponse};
+use std::sync::Arc;
+use validator::Validate;
+
+#[utoipa::path(
+    get,
+    path = "/api/alarms",
+    params(AlarmListRequest),
+    responses(
+        (status = 200, description = "Alarms, most recently raised first", body = [AlarmResponse]),
+        (status = 400, description = "Bad Request", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    tag = "Alarms"
+)]
+#[get("/api/alarms")]
+pub async fn fetch_alarms(
+    req: web::Query<AlarmListRequest>,
+    service: web::Data<Arc<AlarmService>>,
+) -> Result<HttpResponse, ServiceError> {
+    let req = req.into_inner();
+
+    req.validate()
+        .map_err(|e| ServiceError::BadRequest(e.to_string()))?;
+
+    let alarms = service.get_alarms(req).await?;
+    Ok(HttpResponse::Ok().json(alarms))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/alarms/{id}",
+    params(
+        ("id" = i64, Path, description = "ID of the alarm to fetch")
+    ),
+    responses(
+        (status = 200, description = "Alarm fetched successfully", body = AlarmResponse),
+        (status = 404, description = "Alarm not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    tag = "Alarms"
+)]
+#[get("/api/alarms/{id}")]
+pub async fn fetch_alarm(
+    id: web::Path<i64>,
+    service: web::Data<Arc<AlarmService>>,
+) -> Result<HttpResponse, ServiceError> {
+    let alarm = service.get_alarm(&id.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(alarm))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/alarms/{id}/acknowledge",
+    params(
+        ("id" = i64, Path, description = "ID of the alarm to acknowledge")
+    ),
+    request_body(
+        content = AlarmAcknowledgeRequest,
+        example = json!({ "operator": "jdoe" })
+    ),
+    responses(
+        (status = 200, description = "Alarm acknowledged", body = AlarmResponse),
+        (status = 400, description = "Bad Request", body = String),
+        (status = 404, description = "Alarm not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    tag = "Alarms"
+)]
+#[post("/api/alarms/{id}/acknowledge")]
+pub async fn acknowledge_alarm(
+    id: web::Path<i64>,
+    req_body: web::Json<AlarmAcknowledgeRequest>,
+    service: web::Data<Arc<AlarmService>>,
+) -> Result<HttpResponse, ServiceError> {
+    let req = req_body.into_inner();
+
+    req.validate()
+        .map_err(|e| ServiceError::BadRequest(e.to_string()))?;
+
+    let alarm = service
+        .acknowledge_alarm(&id.into_inner(), &req.operator)
+        .await?;
+    Ok(HttpResponse::Ok().json(alarm))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/satellites/{id}/telemetry/limits",
+    params(
+        ("id" = i64, Path, description = "ID of the satellite whose limits are requested")
+    ),
+    responses(
+        (status = 200, description = "Success", body = [TelemetryLimit]),
+        (status = 404, description = "Satellite not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    tag = "Alarms"
+)]
+#[get("/api/satellites/{id}/telemetry/limits")]
+pub async fn get_telemetry_limits(
+    id: web::Path<i64>,
+    service: web::Data<Arc<AlarmService>>,
+) -> Result<HttpResponse, ServiceError> {
+    let limits = service.get_limits(&id.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(limits))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/satellites/{id}/telemetry/limits",
+    params(
+        ("id" = i64, Path, description = "ID of the satellite whose limits are being set")
+    ),
+    request_body(
+        content = [TelemetryLimit],
+        example = json!([
+            { "parameter": "battery_level", "redLow": 10.0, "yellowLow": 25.0, "persistence": 3 },
+            { "parameter": "temperature", "yellowLow": -10.0, "yellowHigh": 45.0, "redLow": -20.0, "redHigh": 60.0 }
+        ])
+    ),
+    responses(
+        (status = 200, description = "Limits saved", body = [TelemetryLimit]),
+        (status = 400, description = "Bad Request", body = String),
+        (status = 404, description = "Satellite not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    tag = "Alarms"
+)]
+#[put("/api/satellites/{id}/telemetry/limits")]
+pub async fn set_telemetry_limits(
+    id: web::Path<i64>,
+    req_body: web::Json<Vec<TelemetryLimit>>,
+    service: web::Data<Arc<AlarmService>>,
+) -> Result<HttpResponse, ServiceError> {
+    let limits = service
+        .set_limits(&id.into_inner(), req_body.into_inner())
+        .await?;
+    Ok(HttpResponse::Ok().json(limits))
+}
@@ -17,6 +17,10 @@ pub async fn get_config(config: web::Data<SharedConfig>) -> Result<impl Responde
         server: config.server.clone(),
         database: config.database.clone(),
         message_broker: config.message_broker.clone(),
+        jobs: config.jobs.clone(),
+        telemetry: config.telemetry.clone(),
+        ingest: config.ingest.clone(),
+        ground_stations: config.ground_stations.clone(),
     };
     Ok(actix_web::web::Json(response))
 }
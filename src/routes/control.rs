@@ -1,12 +1,16 @@
+use crate::messaging::ingest::IngestStats;
 use crate::models::commands::TestMessage;
+use crate::models::requests::ControlCommandRequest;
+use crate::models::responses::{BrokerStatusResponse, IngestMetricsResponse};
 use crate::services::message_service::MessageService;
-use actix_web::{post, web, Responder, Result};
+use actix_web::{get, post, web, HttpResponse, Responder, Result};
 use log::error;
 use std::sync::Arc;
 
 #[utoipa::path(
     post,
     path = "/api/control/command",
+    params(ControlCommandRequest),
     request_body = TestMessage,
     responses(
         (status = 200, description = "Success", body = Vec<TelemetryResponse>),
@@ -17,6 +21,7 @@ use std::sync::Arc;
 )]
 #[post("/api/control/command")]
 pub async fn send_command(
+    query: web::Query<ControlCommandRequest>,
     req_body: web::Json<TestMessage>,
     service: web::Data<Arc<MessageService>>,
 ) -> Result<impl Responder> {
@@ -24,15 +29,17 @@ pub async fn send_command(
     let command = req_body;
     let command_str = serde_json::to_string(&command);
     match command_str {
-        Ok(command_str) => match service.send_message("test-topic", &command_str).await {
-            Ok(_) => Ok(actix_web::HttpResponse::Ok().body("Message sent successfully")),
-            Err(e) => {
-                error!("Error sending message: {}", e);
-                Err(actix_web::error::ErrorInternalServerError(
-                    "Failed to send message",
-                ))
+        Ok(command_str) => {
+            match service.send_message(&service.topics.commands(query.gs_id), &command_str) {
+                Ok(_) => Ok(actix_web::HttpResponse::Ok().body("Message sent successfully")),
+                Err(e) => {
+                    error!("Error sending message: {}", e);
+                    Err(actix_web::error::ErrorInternalServerError(
+                        "Failed to send message",
+                    ))
+                }
             }
-        },
+        }
         Err(e) => {
             error!("Error serializing command: {}", e);
             Err(actix_web::error::ErrorBadRequest(
@@ -41,3 +48,29 @@ pub async fn send_command(
         }
     }
 }
+
+#[utoipa::path(
+    get,
+    path = "/api/control/broker",
+    responses(
+        (status = 200, description = "Success", body = BrokerStatusResponse)
+    ),
+    tag = "API"
+)]
+#[get("/api/control/broker")]
+pub async fn get_broker_status(service: web::Data<Arc<MessageService>>) -> impl Responder {
+    HttpResponse::Ok().json(BrokerStatusResponse::from(service.connection_status()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/control/ingest",
+    responses(
+        (status = 200, description = "Success", body = IngestMetricsResponse)
+    ),
+    tag = "API"
+)]
+#[get("/api/control/ingest")]
+pub async fn get_ingest_metrics(stats: web::Data<IngestStats>) -> impl Responder {
+    HttpResponse::Ok().json(IngestMetricsResponse::from(stats.metrics()))
+}
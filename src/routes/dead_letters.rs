@@ -0,0 +1,100 @@
+use crate::models::requests::{DeadLetterListRequest, DeadLetterReplayRequest};
+use crate::services::{dead_letter_service::DeadLetterService, errors::ServiceError};
+use actix_web::{get, post, web, HttpResponse};
+use std::sync::Arc;
+use validator::Validate;
+
+#[utoipa::path(
+    get,
+    path = "/api/dead-letters",
+    params(DeadLetterListRequest),
+    responses(
+        (status = 200, description = "Dead letters, most recently received first", body = [DeadLetterResponse]),
+        (status = 400, description = "Bad Request", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    tag = "Dead Letters"
+)]
+#[get("/api/dead-letters")]
+pub async fn fetch_dead_letters(
+    req: web::Query<DeadLetterListRequest>,
+    service: web::Data<Arc<DeadLetterService>>,
+) -> Result<HttpResponse, ServiceError> {
+    let req = req.into_inner();
+
+    req.validate()
+        .map_err(|e| ServiceError::BadRequest(e.to_string()))?;
+
+    let dead_letters = service.get_dead_letters(req).await?;
+    Ok(HttpResponse::Ok().json(dead_letters))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/dead-letters/{id}",
+    params(
+        ("id" = i64, Path, description = "ID of the dead letter to fetch")
+    ),
+    responses(
+        (status = 200, description = "Dead letter fetched successfully", body = DeadLetterResponse),
+        (status = 404, description = "Dead letter not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    tag = "Dead Letters"
+)]
+#[get("/api/dead-letters/{id}")]
+pub async fn fetch_dead_letter(
+    id: web::Path<i64>,
+    service: web::Data<Arc<DeadLetterService>>,
+) -> Result<HttpResponse, ServiceError> {
+    let dead_letter = service.get_dead_letter(&id.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(dead_letter))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/dead-letters/{id}/replay",
+    params(
+        ("id" = i64, Path, description = "ID of the dead letter to replay")
+    ),
+    responses(
+        (status = 200, description = "Dead letter after the replay. A failed replay leaves `replayed_at` unset and updates `error`.", body = DeadLetterResponse),
+        (status = 404, description = "Dead letter not found", body = String),
+        (status = 409, description = "Dead letter already replayed or being replayed", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    tag = "Dead Letters"
+)]
+#[post("/api/dead-letters/{id}/replay")]
+pub async fn replay_dead_letter(
+    id: web::Path<i64>,
+    service: web::Data<Arc<DeadLetterService>>,
+) -> Result<HttpResponse, ServiceError> {
+    let dead_letter = service.replay(&id.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(dead_letter))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/dead-letters/replay",
+    params(DeadLetterReplayRequest),
+    responses(
+        (status = 200, description = "Pending dead letters replayed, oldest first", body = DeadLetterReplayResponse),
+        (status = 400, description = "Bad Request", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    tag = "Dead Letters"
+)]
+#[post("/api/dead-letters/replay")]
+pub async fn replay_dead_letters(
+    req: web::Query<DeadLetterReplayRequest>,
+    service: web::Data<Arc<DeadLetterService>>,
+) -> Result<HttpResponse, ServiceError> {
+    let req = req.into_inner();
+
+    req.validate()
+        .map_err(|e| ServiceError::BadRequest(e.to_string()))?;
+
+    let result = service.replay_pending(req).await?;
+    Ok(HttpResponse::Ok().json(result))
+}
@@ -1,6 +1,6 @@
 use crate::models::{entities::GroundStation, requests::GroundStationCreateRequest};
-use crate::services::ground_station_service::GroundStationService;
-use actix_web::{get, post, put, web, Responder, Result};
+use crate::services::{errors::ServiceError, ground_station_service::GroundStationService};
+use actix_web::{get, post, put, web, HttpResponse, Responder, Result};
 use log::error;
 use std::sync::Arc;
 
@@ -37,7 +37,7 @@ pub async fn create_ground_station(
     get,
     path = "/api/ground-stations",
     responses(
-        (status = 200, description = "Success", body = Vec<GroundStation>),
+        (status = 200, description = "Success", body = Vec<GroundStationResponse>),
         (status = 500, description = "Internal Server Error", body = String)
     ),
     tag = "Ground Stations"
@@ -95,6 +95,28 @@ pub async fn fetch_ground_station(
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/ground-stations/{id}/status",
+    params(
+        ("id" = i64, Path, description = "ID of the ground station"),
+    ),
+    responses(
+        (status = 200, description = "Success", body = GroundStationStatusResponse),
+        (status = 404, description = "Not Found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    tag = "Ground Stations"
+)]
+#[get("/api/ground-stations/{id}/status")]
+pub async fn fetch_ground_station_status(
+    id: web::Path<i64>,
+    service: web::Data<Arc<GroundStationService>>,
+) -> Result<HttpResponse, ServiceError> {
+    let status = service.get_status(&id.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(status))
+}
+
 #[utoipa::path(
     put,
     path = "/api/ground-stations/{id}/satellite",
@@ -1,8 +1,8 @@
-use crate::models::requests::JobCreateRequest;
-use crate::services::job_service::JobService;
-use actix_web::{post, web, Responder, Result};
-use log::error;
+use crate::models::requests::{JobCreateRequest, JobListRequest};
+use crate::services::{errors::ServiceError, job_service::JobService};
+use actix_web::{get, post, web, HttpResponse};
 use std::sync::Arc;
+use validator::Validate;
 
 #[utoipa::path(
     post,
@@ -11,7 +11,8 @@ use std::sync::Arc;
     responses(
         (status = 201, description = "Created", body = Job),
         (status = 400, description = "Bad Request", body = String),
-        (status = 500, description = "Internal Server Error", body = String)
+        (status = 404, description = "Satellite or ground station not found", body = String),
+        (status = 500, description = "Internal Server Error, or the job could not be dispatched and was not created", body = String)
     ),
     tag = "Jobs"
 )]
@@ -19,19 +20,87 @@ use std::sync::Arc;
 pub async fn create_job(
     req_body: web::Json<JobCreateRequest>,
     service: web::Data<Arc<JobService>>,
-) -> Result<impl Responder> {
-    println!("req_body: {:?}", req_body);
+) -> Result<HttpResponse, ServiceError> {
     let req = req_body.into_inner();
-    match service
-        .create_job(&req.gs_id, &req.sat_id, &req.commands)
-        .await
-    {
-        Ok(job) => Ok(actix_web::web::Json(job)),
-        Err(e) => {
-            error!("Error creating job: {}", e);
-            Err(actix_web::error::ErrorInternalServerError(
-                "Failed to create job",
-            ))
-        }
-    }
+    let job = service
+        .create_job(&req.gs_id, &req.sat_id, &req.commands, req.aos)
+        .await?;
+
+    Ok(HttpResponse::Created().json(job))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/jobs",
+    params(JobListRequest),
+    responses(
+        (status = 200, description = "A page of jobs, most recent first", body = JobPageResponse),
+        (status = 400, description = "Bad Request", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    tag = "Jobs"
+)]
+#[get("/api/jobs")]
+pub async fn fetch_all_jobs(
+    req: web::Query<JobListRequest>,
+    service: web::Data<Arc<JobService>>,
+) -> Result<HttpResponse, ServiceError> {
+    let req = req.into_inner();
+
+    req.validate()
+        .map_err(|e| ServiceError::BadRequest(e.to_string()))?;
+
+    let page = service.get_jobs_page(req).await?;
+    Ok(HttpResponse::Ok().json(page))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/jobs/{id}",
+    params(
+        ("id" = i64, Path, description = "ID of the job to fetch")
+    ),
+    responses(
+        (status = 200, description = "Job fetched successfully", body = Job),
+        (status = 404, description = "Job not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    tag = "Jobs"
+)]
+#[get("/api/jobs/{id}")]
+pub async fn fetch_job(
+    id: web::Path<i64>,
+    service: web::Data<Arc<JobService>>,
+) -> Result<HttpResponse, ServiceError> {
+    let id = id.into_inner();
+    let job = service
+        .get_job(&id)
+        .await?
+        .ok_or_else(|| ServiceError::NotFound(format!("Job {id} not found")))?;
+
+    Ok(HttpResponse::Ok().json(job))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/jobs/{id}/status",
+    params(
+        ("id" = i64, Path, description = "ID of the job whose status is requested")
+    ),
+    responses(
+        (status = 200, description = "Latest status and status history", body = JobStatusResponse),
+        (status = 404, description = "Job not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    tag = "Jobs"
+)]
+#[get("/api/jobs/{id}/status")]
+pub async fn fetch_job_status(
+    id: web::Path<i64>,
+    service: web::Data<Arc<JobService>>,
+) -> Result<HttpResponse, ServiceError> {
+    let id = id.into_inner();
+    let status = service.get_job_status(&id).await?;
+
+    Ok(HttpResponse::Ok().json(status))
 }
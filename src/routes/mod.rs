@@ -1,6 +1,9 @@
+pub mod alarms;
 pub mod config;
 pub mod control;
+pub mod dead_letters;
 pub mod ground_stations;
 pub mod jobs;
 pub mod satellites;
 pub mod telemetry;
+pub mod tracking;
@@ -1,4 +1,4 @@
-use crate::models::entities::Satellite;
+use crate::models::entities::{CcsdsConfig, Satellite};
 use crate::models::requests::{SatelliteCreateRequest, TleUpdateRequest};
 use crate::services::{errors::ServiceError, satellite_service::SatelliteService};
 use actix_web::{delete, get, post, put, web, HttpResponse};
@@ -89,6 +89,63 @@ pub async fn update_satellite_tle(
     Ok(HttpResponse::Ok().json(sat))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/satellites/{id}/ccsds",
+    params(
+        ("id" = i64, Path, description = "ID of the satellite whose CCSDS settings are requested")
+    ),
+    responses(
+        (status = 200, description = "Success", body = CcsdsConfig),
+        (status = 404, description = "Satellite not found or not using CCSDS framing", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    tag = "Satellites"
+)]
+#[get("/api/satellites/{id}/ccsds")]
+pub async fn get_ccsds_config(
+    id: web::Path<i64>,
+    service: web::Data<Arc<SatelliteService>>,
+) -> Result<HttpResponse, ServiceError> {
+    let config = service.get_ccsds_config(&id.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(config))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/satellites/{id}/ccsds",
+    params(
+        ("id" = i64, Path, description = "ID of the satellite whose CCSDS settings are being set")
+    ),
+    request_body(
+        content = CcsdsConfig,
+        example = json!({
+            "spacecraftId": 42,
+            "fecf": true
+        })
+    ),
+    responses(
+        (status = 200, description = "CCSDS settings saved", body = CcsdsConfig),
+        (status = 400, description = "Bad Request", body = String),
+        (status = 404, description = "Satellite not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    tag = "Satellites"
+)]
+#[put("/api/satellites/{id}/ccsds")]
+pub async fn set_ccsds_config(
+    id: web::Path<i64>,
+    req_body: web::Json<CcsdsConfig>,
+    service: web::Data<Arc<SatelliteService>>,
+) -> Result<HttpResponse, ServiceError> {
+    let config = service
+        .set_ccsds_config(&id.into_inner(), req_body.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(config))
+}
+
 #[utoipa::path(
     post,
     path = "/api/satellites",
@@ -1,20 +1,30 @@
-use crate::models::requests::{HistoricTelemetryRequest, LatestTelemetryRequest};
-use crate::services::telemetry_service::TelemetryService;
-use actix_web::{get, web, Responder, Result};
-use log::error;
+use crate::models::entities::TelemetryDecoder;
+use crate::models::requests::{
+    ExportFormat, HistoricTelemetryRequest, LatestTelemetryRequest, LiveTelemetryRequest,
+    TelemetryAggregateRequest, TelemetryExportRequest, TelemetryPageRequest,
+};
+use crate::services::{errors::ServiceError, telemetry_service::TelemetryService};
+use actix_web::{get, http::header, put, web, HttpRequest, HttpResponse};
 use std::sync::Arc;
+use std::time::Duration;
+use validator::Validate;
+
+/// Idle time after which a comment is sent to keep live streams open through proxies
+const LIVE_KEEP_ALIVE: Duration = Duration::from_secs(15);
 
 /// Latest telemetry endpoint
 #[utoipa::path(
     get,
     path = "/api/telemetry/{satellite}/latest",
     params(
-        ("satellite" = String, Path, description = "Name of satellite to fetch telemetry from"),
-        ("amount" = Option<i32>, Query, description = "Number of items to return", example = 10),
+        ("satellite" = String, Path, description = "ID or name of satellite to fetch telemetry from"),
+        ("amount" = Option<i32>, Query, description = "Number of items to return, between 1 and 1000", example = 10),
+        ("raw" = Option<RawEncoding>, Query, description = "Include the raw frame encoded as hex or base64"),
     ),
     responses(
         (status = 200, description = "Success", body = Vec<TelemetryResponse>),
         (status = 400, description = "Bad Request", body = String),
+        (status = 404, description = "Satellite not found", body = String),
         (status = 500, description = "Internal Server Error", body = String)
     ),
     tag = "Telemetry"
@@ -24,22 +34,19 @@ pub async fn get_latest_telemetry(
     satellite: web::Path<String>,
     req: web::Query<LatestTelemetryRequest>,
     service: web::Data<Arc<TelemetryService>>,
-) -> Result<impl Responder> {
+) -> Result<HttpResponse, ServiceError> {
     let req = req.into_inner();
+
+    req.validate()
+        .map_err(|e| ServiceError::BadRequest(e.to_string()))?;
+
     let amount = req.amount.unwrap_or(10);
 
-    match service
-        .get_latest_telemetry(satellite.into_inner(), amount)
-        .await
-    {
-        Ok(telemetry) => Ok(actix_web::web::Json(telemetry)),
-        Err(e) => {
-            error!("Error fetching latest telemetry: {}", e);
-            Err(actix_web::error::ErrorInternalServerError(
-                "Failed to fetch telemetry data",
-            ))
-        }
-    }
+    let telemetry = service
+        .get_latest_telemetry(satellite.into_inner(), amount, req.raw)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(telemetry))
 }
 
 /// Historic telemetry endpoint
@@ -47,13 +54,15 @@ pub async fn get_latest_telemetry(
     get,
     path = "/api/telemetry/{satellite}/history",
     params(
-        ("satellite" = String, Path, description = "Name of satellite to fetch telemetry from"),
+        ("satellite" = String, Path, description = "ID or name of satellite to fetch telemetry from"),
         ("startTime" = Option<i64>, Query, description = "Start timestamp", example = 1640995200),
         ("endTime" = Option<i64>, Query, description = "End timestamp", example = 1640998800),
+        ("raw" = Option<RawEncoding>, Query, description = "Include the raw frame encoded as hex or base64"),
     ),
     responses(
         (status = 200, description = "Success", body = Vec<TelemetryResponse>),
         (status = 400, description = "Bad Request", body = String),
+        (status = 404, description = "Satellite not found", body = String),
         (status = 500, description = "Internal Server Error", body = String)
     ),
     tag = "Telemetry"
@@ -63,19 +72,344 @@ pub async fn get_historic_telemetry(
     satellite: web::Path<String>,
     req: web::Query<HistoricTelemetryRequest>,
     service: web::Data<Arc<TelemetryService>>,
-) -> Result<impl Responder> {
+) -> Result<HttpResponse, ServiceError> {
+    let req = req.into_inner();
+
+    let telemetry = service
+        .get_historic_telemetry(
+            satellite.into_inner(),
+            req.start_time,
+            req.end_time,
+            req.raw,
+        )
+        .await?;
+
+    Ok(HttpResponse::Ok().json(telemetry))
+}
+
+/// Aggregated telemetry endpoint
+#[utoipa::path(
+    get,
+    path = "/api/telemetry/{satellite}/aggregate",
+    params(
+        ("satellite" = String, Path, description = "ID or name of satellite to aggregate telemetry of"),
+        TelemetryAggregateRequest
+    ),
+    responses(
+        (status = 200, description = "Success", body = TelemetryAggregateResponse),
+        (status = 400, description = "Bad Request", body = String),
+        (status = 404, description = "Satellite not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    tag = "Telemetry"
+)]
+#[get("/api/telemetry/{satellite}/aggregate")]
+pub async fn get_aggregated_telemetry(
+    satellite: web::Path<String>,
+    req: web::Query<TelemetryAggregateRequest>,
+    service: web::Data<Arc<TelemetryService>>,
+) -> Result<HttpResponse, ServiceError> {
+    let req = req.into_inner();
+
+    req.validate()
+        .map_err(|e| ServiceError::BadRequest(e.to_string()))?;
+
+    let aggregates = service
+        .aggregate_telemetry(satellite.into_inner(), req)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(aggregates))
+}
+
+/// Telemetry export endpoint
+#[utoipa::path(
+    get,
+    path = "/api/telemetry/{satellite}/export",
+    params(
+        ("satellite" = String, Path, description = "ID or name of satellite to export telemetry of"),
+        TelemetryExportRequest
+    ),
+    responses(
+        (status = 200, description = "Telemetry rows, streamed", content_type = ["text/csv", "application/x-ndjson"], body = String),
+        (status = 400, description = "Bad Request", body = String),
+        (status = 404, description = "Satellite not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    tag = "Telemetry"
+)]
+#[get("/api/telemetry/{satellite}/export")]
+pub async fn export_telemetry(
+    satellite: web::Path<String>,
+    req: web::Query<TelemetryExportRequest>,
+    http_req: HttpRequest,
+    service: web::Data<Arc<TelemetryService>>,
+) -> Result<HttpResponse, ServiceError> {
+    let satellite = satellite.into_inner();
     let req = req.into_inner();
+    let format = req
+        .format
+        .or_else(|| accepted_format(&http_req))
+        .unwrap_or(ExportFormat::Ndjson);
+
+    let rows = service
+        .get_ref()
+        .export_telemetry(satellite.clone(), req, format)
+        .await?;
+    let body = futures::stream::unfold(rows, |mut rows| async move {
+        rows.recv()
+            .await
+            .map(|row| (row.map(web::Bytes::from), rows))
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type(format.content_type())
+        .insert_header((
+            header::CONTENT_DISPOSITION,
+            format!(
+                "attachment; filename=\"telemetry-{}.{}\"",
+                satellite,
+                format.extension()
+            ),
+        ))
+        .streaming(body))
+}
+
+/// Live telemetry endpoint, streamed as Server-Sent Events
+#[utoipa::path(
+    get,
+    path = "/api/telemetry/{satellite}/live",
+    params(
+        ("satellite" = String, Path, description = "ID or name of satellite to stream telemetry of"),
+        LiveTelemetryRequest
+    ),
+    responses(
+        (status = 200, description = "`telemetry` events carrying a TelemetryResponse as JSON", content_type = "text/event-stream", body = String),
+        (status = 404, description = "Satellite not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    tag = "Telemetry"
+)]
+#[get("/api/telemetry/{satellite}/live")]
+pub async fn stream_live_telemetry(
+    satellite: web::Path<String>,
+    req: web::Query<LiveTelemetryRequest>,
+    service: web::Data<Arc<TelemetryService>>,
+) -> Result<HttpResponse, ServiceError> {
+    let live = service
+        .subscribe_live(satellite.into_inner(), req.into_inner().parameters)
+        .await?;
+
+    let events = futures::stream::unfold(live, |mut live| async move {
+        let event = match tokio::time::timeout(LIVE_KEEP_ALIVE, live.next()).await {
+            Ok(Some(sample)) => match serde_json::to_string(&sample) {
+                Ok(json) => format!("event: telemetry\ndata: {}\n\n", json),
+                Err(e) => return Some((Err(ServiceError::Internal(e.to_string())), live)),
+            },
+            Ok(None) => return None,
+            Err(_) => ": keep-alive\n\n".to_string(),
+        };
+        Some((Ok(web::Bytes::from(event)), live))
+    });
 
-    match service
-        .get_historic_telemetry(satellite.into_inner(), req.start_time, req.end_time)
-        .await
-    {
-        Ok(telemetry) => Ok(actix_web::web::Json(telemetry)),
-        Err(e) => {
-            error!("Error fetching historic telemetry: {}", e);
-            Err(actix_web::error::ErrorInternalServerError(
-                "Failed to fetch telemetry data",
-            ))
-        }
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        .streaming(events))
+}
+
+/// Pick the export format from the `Accept` header
+fn accepted_format(req: &HttpRequest) -> Option<ExportFormat> {
+    let accept = req.headers().get(header::ACCEPT)?.to_str().ok()?;
+    if accept.contains("text/csv") {
+        Some(ExportFormat::Csv)
+    } else if accept.contains("ndjson") {
+        Some(ExportFormat::Ndjson)
+    } else {
+        None
     }
 }
+
+/// Paginated telemetry endpoint
+#[utoipa::path(
+    get,
+    path = "/api/satellites/{satellite}/telemetry",
+    params(
+        ("satellite" = String, Path, description = "ID or name of satellite to fetch telemetry from"),
+        TelemetryPageRequest
+    ),
+    responses(
+        (status = 200, description = "Success", body = TelemetryPageResponse),
+        (status = 400, description = "Bad Request", body = String),
+        (status = 404, description = "Satellite not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    tag = "Telemetry"
+)]
+#[get("/api/satellites/{satellite}/telemetry")]
+pub async fn get_telemetry_page(
+    satellite: web::Path<String>,
+    req: web::Query<TelemetryPageRequest>,
+    service: web::Data<Arc<TelemetryService>>,
+) -> Result<HttpResponse, ServiceError> {
+    let req = req.into_inner();
+
+    req.validate()
+        .map_err(|e| ServiceError::BadRequest(e.to_string()))?;
+
+    let page = service
+        .get_telemetry_page(satellite.into_inner(), req)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(page))
+}
+
+/// Ground station receptions of a stored frame
+#[utoipa::path(
+    get,
+    path = "/api/telemetry/frames/{id}/receptions",
+    params(
+        ("id" = i64, Path, description = "ID of the stored telemetry frame")
+    ),
+    responses(
+        (status = 200, description = "Receptions, earliest first", body = [TelemetryReceptionResponse]),
+        (status = 404, description = "Telemetry not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    tag = "Telemetry"
+)]
+#[get("/api/telemetry/frames/{id}/receptions")]
+pub async fn get_telemetry_receptions(
+    id: web::Path<i64>,
+    service: web::Data<Arc<TelemetryService>>,
+) -> Result<HttpResponse, ServiceError> {
+    let receptions = service.get_receptions(&id.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(receptions))
+}
+
+/// Telemetry decoder of a satellite
+#[utoipa::path(
+    get,
+    path = "/api/satellites/{id}/telemetry/decoder",
+    params(
+        ("id" = i64, Path, description = "ID of the satellite whose decoder is requested")
+    ),
+    responses(
+        (status = 200, description = "Success", body = TelemetryDecoder),
+        (status = 404, description = "Satellite or decoder not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    tag = "Telemetry"
+)]
+#[get("/api/satellites/{id}/telemetry/decoder")]
+pub async fn get_telemetry_decoder(
+    id: web::Path<i64>,
+    service: web::Data<Arc<TelemetryService>>,
+) -> Result<HttpResponse, ServiceError> {
+    let decoder = service.get_decoder(&id.into_inner(), None).await?;
+
+    Ok(HttpResponse::Ok().json(decoder))
+}
+
+/// Create or replace the telemetry decoder of a satellite
+#[utoipa::path(
+    put,
+    path = "/api/satellites/{id}/telemetry/decoder",
+    params(
+        ("id" = i64, Path, description = "ID of the satellite whose decoder is being set")
+    ),
+    request_body(
+        content = TelemetryDecoder,
+        example = json!({
+            "fields": [
+                { "name": "battery_voltage", "byteOffset": 0, "width": 2, "scale": 0.001, "unit": "V" },
+                { "name": "temperature", "byteOffset": 2, "width": 2, "signed": true, "scale": 0.1, "unit": "degC" },
+                { "name": "uptime", "byteOffset": 4, "width": 4, "endianness": "little", "unit": "s" }
+            ]
+        })
+    ),
+    responses(
+        (status = 200, description = "Decoder saved", body = TelemetryDecoder),
+        (status = 400, description = "Bad Request", body = String),
+        (status = 404, description = "Satellite not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    tag = "Telemetry"
+)]
+#[put("/api/satellites/{id}/telemetry/decoder")]
+pub async fn set_telemetry_decoder(
+    id: web::Path<i64>,
+    req_body: web::Json<TelemetryDecoder>,
+    service: web::Data<Arc<TelemetryService>>,
+) -> Result<HttpResponse, ServiceError> {
+    let decoder = service
+        .set_decoder(&id.into_inner(), None, req_body.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(decoder))
+}
+
+/// Telemetry decoder of one APID of a satellite
+#[utoipa::path(
+    get,
+    path = "/api/satellites/{id}/telemetry/decoders/{apid}",
+    params(
+        ("id" = i64, Path, description = "ID of the satellite whose decoder is requested"),
+        ("apid" = i32, Path, description = "APID of the space packets the decoder applies to")
+    ),
+    responses(
+        (status = 200, description = "Success", body = TelemetryDecoder),
+        (status = 404, description = "Satellite or decoder not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    tag = "Telemetry"
+)]
+#[get("/api/satellites/{id}/telemetry/decoders/{apid}")]
+pub async fn get_apid_decoder(
+    path: web::Path<(i64, i32)>,
+    service: web::Data<Arc<TelemetryService>>,
+) -> Result<HttpResponse, ServiceError> {
+    let (id, apid) = path.into_inner();
+    let decoder = service.get_decoder(&id, Some(apid)).await?;
+
+    Ok(HttpResponse::Ok().json(decoder))
+}
+
+/// Create or replace the telemetry decoder of one APID of a satellite
+#[utoipa::path(
+    put,
+    path = "/api/satellites/{id}/telemetry/decoders/{apid}",
+    params(
+        ("id" = i64, Path, description = "ID of the satellite whose decoder is being set"),
+        ("apid" = i32, Path, description = "APID of the space packets the decoder applies to")
+    ),
+    request_body(
+        content = TelemetryDecoder,
+        example = json!({
+            "fields": [
+                { "name": "battery_voltage", "byteOffset": 6, "width": 2, "scale": 0.001, "unit": "V" },
+                { "name": "obc_temperature", "byteOffset": 8, "width": 2, "signed": true, "scale": 0.1, "unit": "degC" }
+            ]
+        })
+    ),
+    responses(
+        (status = 200, description = "Decoder saved", body = TelemetryDecoder),
+        (status = 400, description = "Bad Request", body = String),
+        (status = 404, description = "Satellite not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    tag = "Telemetry"
+)]
+#[put("/api/satellites/{id}/telemetry/decoders/{apid}")]
+pub async fn set_apid_decoder(
+    path: web::Path<(i64, i32)>,
+    req_body: web::Json<TelemetryDecoder>,
+    service: web::Data<Arc<TelemetryService>>,
+) -> Result<HttpResponse, ServiceError> {
+    let (id, apid) = path.into_inner();
+    let decoder = service
+        .set_decoder(&id, Some(apid), req_body.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(decoder))
+}
@@ -0,0 +1,83 @@
+use crate::models::requests::PassPredictionRequest;
+use crate::services::{errors::ServiceError, tracking_service::TrackingService};
+use actix_web::{get, web, HttpResponse};
+use std::sync::Arc;
+use validator::Validate;
+
+#[utoipa::path(
+    get,
+    path = "/api/satellites/{id}/passes",
+    params(
+        ("id" = i64, Path, description = "ID of the satellite to predict passes for"),
+        PassPredictionRequest
+    ),
+    responses(
+        (status = 200, description = "Upcoming passes over every ground station", body = [PassResponse]),
+        (status = 400, description = "Bad Request", body = String),
+        (status = 404, description = "Satellite not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    tag = "Tracking"
+)]
+#[get("/api/satellites/{id}/passes")]
+pub async fn get_satellite_passes(
+    id: web::Path<i64>,
+    req: web::Query<PassPredictionRequest>,
+    service: web::Data<Arc<TrackingService>>,
+) -> Result<HttpResponse, ServiceError> {
+    let id = id.into_inner();
+    let req = req.into_inner();
+
+    req.validate()
+        .map_err(|e| ServiceError::BadRequest(e.to_string()))?;
+
+    let passes = service
+        .get_satellite_passes(
+            &id,
+            req.start_time,
+            req.end_time,
+            req.min_elevation.unwrap_or(0.0),
+        )
+        .await?;
+
+    Ok(HttpResponse::Ok().json(passes))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/ground-stations/{id}/passes",
+    params(
+        ("id" = i64, Path, description = "ID of the ground station to predict passes for"),
+        PassPredictionRequest
+    ),
+    responses(
+        (status = 200, description = "Upcoming passes of every satellite", body = [PassResponse]),
+        (status = 400, description = "Bad Request", body = String),
+        (status = 404, description = "Ground station not found", body = String),
+        (status = 500, description = "Internal Server Error", body = String)
+    ),
+    tag = "Tracking"
+)]
+#[get("/api/ground-stations/{id}/passes")]
+pub async fn get_ground_station_passes(
+    id: web::Path<i64>,
+    req: web::Query<PassPredictionRequest>,
+    service: web::Data<Arc<TrackingService>>,
+) -> Result<HttpResponse, ServiceError> {
+    let id = id.into_inner();
+    let req = req.into_inner();
+
+    req.validate()
+        .map_err(|e| ServiceError::BadRequest(e.to_string()))?;
+
+    let passes = service
+        .get_ground_station_passes(
+            &id,
+            req.start_time,
+            req.end_time,
+            req.min_elevation.unwrap_or(0.0),
+        )
+        .await?;
+
+    Ok(HttpResponse::Ok().json(passes))
+}
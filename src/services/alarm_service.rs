@@ -0,0 +1,385 @@
+use crate::models::{
+    entities::TelemetryLimit,
+    requests::AlarmListRequest,
+    responses::{AlarmResponse, TelemetryParameter},
+};
+use crate::repository::alarm::{AlarmFilter, AlarmRepository};
+use crate::repository::satellite::SatelliteRepository;
+use crate::repository::telemetry_limit::TelemetryLimitRepository;
+use crate::services::errors::ServiceError;
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+const DEFAULT_ALARM_LIMIT: i64 = 100;
+
+/// Limits of the satellites that sent telemetry, so samples are not checked against a
+/// database read each. `generation` is bumped whenever limits change, so a read that
+/// started before the change is not cached.
+#[derive(Default)]
+struct LimitCache {
+    generation: u64,
+    by_satellite: HashMap<i64, Arc<Vec<TelemetryLimit>>>,
+}
+
+pub struct AlarmService {
+    alarm_repository: AlarmRepository,
+    limit_repository: TelemetryLimitRepository,
+    satellite_repository: SatelliteRepository,
+    /// Consecutive violating samples per satellite parameter. Kept in memory, so
+    /// persistence counts restart from zero when the API restarts.
+    violations: Mutex<HashMap<(i64, String), i32>>,
+    limits: Mutex<LimitCache>,
+}
+
+impl AlarmService {
+    pub fn new(
+        alarm_repository: AlarmRepository,
+        limit_repository: TelemetryLimitRepository,
+        satellite_repository: SatelliteRepository,
+    ) -> Self {
+        Self {
+            alarm_repository,
+            limit_repository,
+            satellite_repository,
+            violations: Mutex::new(HashMap::new()),
+            limits: Mutex::new(LimitCache::default()),
+        }
+    }
+
+    /// Limits of a satellite, read from the database on a cache miss
+    async fn cached_limits(&self, sat_id: i64) -> Result<Arc<Vec<TelemetryLimit>>, ServiceError> {
+        let generation = {
+            let cache = self.limits.lock().unwrap();
+            if let Some(limits) = cache.by_satellite.get(&sat_id) {
+                return Ok(limits.clone());
+            }
+            cache.generation
+        };
+
+        let limits = Arc::new(self.limit_repository.get_limits(&sat_id).await?);
+
+        let mut cache = self.limits.lock().unwrap();
+        if cache.generation == generation {
+            cache.by_satellite.insert(sat_id, limits.clone());
+        }
+        Ok(limits)
+    }
+
+    /// Check a decoded sample against the satellite's limits. An alarm is raised once a
+    /// parameter violates a limit for `persistence` consecutive samples, escalated when
+    /// a worse limit is violated, and cleared by the first sample back within limits.
+    /// Invalid values are ignored.
+    pub async fn check_sample(
+        &self,
+        sat_id: i64,
+        timestamp: DateTime<Utc>,
+        parameters: &BTreeMap<String, TelemetryParameter>,
+    ) -> Result<(), ServiceError> {
+        let limits = self.cached_limits(sat_id).await?;
+
+        for limit in limits.iter() {
+            let Some(value) = parameters
+                .get(&limit.parameter)
+                .filter(|param| param.valid)
+                .and_then(|param| param.value)
+            else {
+                continue;
+            };
+            let key = (sat_id, limit.parameter.clone());
+
+            match limit.check(value) {
+                Some(severity) => {
+                    let count = {
+                        let mut violations = self.violations.lock().unwrap();
+                        let count = violations.entry(key).or_insert(0);
+                        *count = count.saturating_add(1);
+                        *count
+                    };
+                    if count < limit.persistence {
+                        continue;
+                    }
+
+                    match self
+                        .alarm_repository
+                        .get_active_alarm(sat_id, &limit.parameter)
+                        .await?
+                    {
+                        None => {
+                            let alarm = self
+                                .alarm_repository
+                                .raise_alarm(sat_id, &limit.parameter, severity, value, timestamp)
+                                .await?;
+                            warn!(
+                                "Alarm {} raised: satellite {} {} = {} ({:?})",
+                                alarm.id, sat_id, limit.parameter, value, severity
+                            );
+                        }
+                        Some(active) if severity > active.severity => {
+                            self.alarm_repository
+                                .escalate_alarm(active.id, severity, value)
+                                .await?;
+                            warn!(
+                                "Alarm {} escalated: satellite {} {} = {} ({:?})",
+                                active.id, sat_id, limit.parameter, value, severity
+                            );
+                        }
+                        Some(_) => {}
+                    }
+                }
+                None => {
+                    self.violations.lock().unwrap().remove(&key);
+
+                    if let Some(active) = self
+                        .alarm_repository
+                        .get_active_alarm(sat_id, &limit.parameter)
+                        .await?
+                    {
+                        self.alarm_repository
+                            .clear_alarm(active.id, timestamp)
+                            .await?;
+                        info!(
+                            "Alarm {} cleared: satellite {} {} = {}",
+                            active.id, sat_id, limit.parameter, value
+                        );
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn get_alarms(
+        &self,
+        req: AlarmListRequest,
+    ) -> Result<Vec<AlarmResponse>, ServiceError> {
+        let alarms = self
+            .alarm_repository
+            .get_alarms(&AlarmFilter {
+                sat_id: req.sat_id,
+                active: req.active,
+                acknowledged: req.acknowledged,
+                limit: req.limit.unwrap_or(DEFAULT_ALARM_LIMIT),
+            })
+            .await?;
+
+        Ok(alarms.into_iter().map(AlarmResponse::from).collect())
+    }
+
+    pub async fn get_alarm(&self, id: &i64) -> Result<AlarmResponse, ServiceError> {
+        self.alarm_repository
+            .get_alarm(id)
+            .await?
+            .map(AlarmResponse::from)
+            .ok_or_else(|| ServiceError::NotFound(format!("Alarm {} not found", id)))
+    }
+
+    /// Acknowledge an alarm on behalf of an operator
+    pub async fn acknowledge_alarm(
+        &self,
+        id: &i64,
+        operator: &str,
+    ) -> Result<AlarmResponse, ServiceError> {
+        self.alarm_repository
+            .acknowledge_alarm(id, operator)
+            .await?
+            .map(AlarmResponse::from)
+            .ok_or_else(|| ServiceError::NotFound(format!("Alarm {} not found", id)))
+    }
+
+    /// Get the telemetry limits configured for a satellite
+    pub async fn get_limits(&self, sat_id: &i64) -> Result<Vec<TelemetryLimit>, ServiceError> {
+        self.satellite_repository
+            .get_satellite(sat_id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("Satellite {} not found", sat_id)))?;
+
+        Ok(self.limit_repository.get_limits(sat_id).await?)
+    }
+
+    /// Replace the telemetry limits of a satellite
+    pub async fn set_limits(
+        &self,
+        sat_id: &i64,
+        limits: Vec<TelemetryLimit>,
+    ) -> Result<Vec<TelemetryLimit>, ServiceError> {
+        validate_limits(&limits).map_err(ServiceError::BadRequest)?;
+
+        self.satellite_repository
+            .get_satellite(sat_id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("Satellite {} not found", sat_id)))?;
+
+        let cleared = self.limit_repository.set_limits(sat_id, &limits).await?;
+        if cleared > 0 {
+            info!(
+                "{} alarm(s) of satellite {} cleared with their removed limits",
+                cleared, sat_id
+            );
+        }
+
+        {
+            let mut cache = self.limits.lock().unwrap();
+            cache.generation += 1;
+            cache.by_satellite.remove(sat_id);
+        }
+        // Persistence counts were made against the old limits
+        self.violations
+            .lock()
+            .unwrap()
+            .retain(|(id, _), _| id != sat_id);
+
+        Ok(limits)
+    }
+}
+
+/// Check that limit definitions are consistent
+fn validate_limits(limits: &[TelemetryLimit]) -> Result<(), String> {
+    let mut names = HashSet::new();
+    for limit in limits {
+        if limit.parameter.trim().is_empty() {
+            return Err("Parameter names cannot be empty".into());
+        }
+        if !names.insert(limit.parameter.as_str()) {
+            return Err(format!("Duplicate limits for '{}'", limit.parameter));
+        }
+        if limit.persistence < 1 {
+            return Err(format!(
+                "Limits of '{}' must have a persistence of at least 1",
+                limit.parameter
+            ));
+        }
+
+        // Set limits must be ordered red low <= yellow low <= yellow high <= red high
+        let bounds: Vec<f64> = [
+            limit.red_low,
+            limit.yellow_low,
+            limit.yellow_high,
+            limit.red_high,
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if bounds.is_empty() {
+            return Err(format!("'{}' must set at least one limit", limit.parameter));
+        }
+        if bounds.iter().any(|b| !b.is_finite()) || bounds.windows(2).any(|w| w[0] > w[1]) {
+            return Err(format!(
+                "Limits of '{}' must be finite and ordered redLow <= yellowLow <= yellowHigh <= redHigh",
+                limit.parameter
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::entities::AlarmSeverity;
+
+    fn limit(bounds: [Option<f64>; 4]) -> TelemetryLimit {
+        let [red_low, yellow_low, yellow_high, red_high] = bounds;
+        TelemetryLimit {
+            parameter: "battery_level".into(),
+            red_low,
+            yellow_low,
+            yellow_high,
+            red_high,
+            persistence: 1,
+        }
+    }
+
+    #[test]
+    fn checks_values_against_limits() {
+        let limit = limit([Some(10.0), Some(25.0), Some(80.0), Some(90.0)]);
+        let cases = [
+            (5.0, Some(AlarmSeverity::Red)),
+            (10.0, Some(AlarmSeverity::Yellow)),
+            (20.0, Some(AlarmSeverity::Yellow)),
+            (25.0, None),
+            (50.0, None),
+            (80.0, None),
+            (85.0, Some(AlarmSeverity::Yellow)),
+            (90.0, Some(AlarmSeverity::Yellow)),
+            (95.0, Some(AlarmSeverity::Red)),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(limit.check(value), expected, "value {}", value);
+        }
+    }
+
+    #[test]
+    fn ignores_unset_limits() {
+        let low_only = limit([None, Some(25.0), None, None]);
+        assert_eq!(low_only.check(20.0), Some(AlarmSeverity::Yellow));
+        assert_eq!(low_only.check(1e9), None);
+
+        let red_high_only = limit([None, None, None, Some(90.0)]);
+        assert_eq!(red_high_only.check(-1e9), None);
+        assert_eq!(red_high_only.check(91.0), Some(AlarmSeverity::Red));
+    }
+
+    #[test]
+    fn accepts_consistent_limits() {
+        let limits = [
+            limit([Some(10.0), Some(25.0), Some(80.0), Some(90.0)]),
+            TelemetryLimit {
+                parameter: "temperature".into(),
+                persistence: 3,
+                ..limit([None, None, Some(60.0), Some(60.0)])
+            },
+        ];
+        assert_eq!(validate_limits(&limits), Ok(()));
+        assert_eq!(validate_limits(&[]), Ok(()));
+    }
+
+    #[test]
+    fn rejects_inconsistent_limits() {
+        let named = |parameter: &str| TelemetryLimit {
+            parameter: parameter.into(),
+            ..limit([Some(10.0), None, None, None])
+        };
+        let cases = [
+            (vec![named(" ")], "Parameter names cannot be empty"),
+            (
+                vec![named("battery_level"), named("battery_level")],
+                "Duplicate limits",
+            ),
+            (
+                vec![TelemetryLimit {
+                    persistence: 0,
+                    ..named("battery_level")
+                }],
+                "persistence of at least 1",
+            ),
+            (
+                vec![limit([None, None, None, None])],
+                "must set at least one limit",
+            ),
+            (
+                vec![limit([Some(30.0), Some(25.0), None, None])],
+                "must be finite and ordered",
+            ),
+            (
+                vec![limit([None, Some(25.0), Some(20.0), None])],
+                "must be finite and ordered",
+            ),
+            (
+                vec![limit([None, None, Some(f64::NAN), None])],
+                "must be finite and ordered",
+            ),
+            (
+                vec![limit([None, None, None, Some(f64::INFINITY)])],
+                "must be finite and ordered",
+            ),
+        ];
+        for (limits, expected) in cases {
+            let error = validate_limits(&limits).unwrap_err();
+            assert!(error.contains(expected), "{:?}: {}", limits, error);
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use crate::services::errors::ServiceError;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+
+/// Keyset pagination cursor holding the `(timestamp, id)` sort key of the last row of
+/// a page, so the next page starts right after it
+pub fn encode_cursor(timestamp: DateTime<Utc>, id: i64) -> String {
+    URL_SAFE_NO_PAD.encode(format!("{}:{}", timestamp.timestamp_micros(), id))
+}
+
+pub fn decode_cursor(cursor: &str) -> Result<(DateTime<Utc>, i64), ServiceError> {
+    let invalid = || ServiceError::BadRequest("Invalid cursor".into());
+
+    let bytes = URL_SAFE_NO_PAD.decode(cursor).map_err(|_| invalid())?;
+    let text = String::from_utf8(bytes).map_err(|_| invalid())?;
+    let (micros, id) = text.split_once(':').ok_or_else(invalid)?;
+    let micros = micros.parse::<i64>().map_err(|_| invalid())?;
+    let id = id.parse::<i64>().map_err(|_| invalid())?;
+
+    Ok((
+        DateTime::from_timestamp_micros(micros).ok_or_else(invalid)?,
+        id,
+    ))
+}
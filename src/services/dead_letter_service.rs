@@ -0,0 +1,148 @@
+use crate::messaging::handler::MessageHandler;
+use crate::models::{
+    requests::{DeadLetterListRequest, DeadLetterReplayRequest},
+    responses::{DeadLetterReplayResponse, DeadLetterResponse},
+};
+use crate::repository::dead_letter::{DeadLetterFilter, DeadLetterRepository};
+use crate::services::errors::ServiceError;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+
+const DEFAULT_DEAD_LETTER_LIMIT: i64 = 100;
+
+pub struct DeadLetterService {
+    repository: DeadLetterRepository,
+    handler: Arc<MessageHandler>,
+}
+
+impl DeadLetterService {
+    pub fn new(repository: DeadLetterRepository, handler: Arc<MessageHandler>) -> Self {
+        Self {
+            repository,
+            handler,
+        }
+    }
+
+    /// Keep a message that could not be processed
+    pub async fn record(
+        &self,
+        topic: &str,
+        payload: &[u8],
+        error: &str,
+        received_at: DateTime<Utc>,
+    ) -> Result<(), ServiceError> {
+        let dead_letter = self
+            .repository
+            .create_dead_letter(topic, payload, error, received_at)
+            .await?;
+        println!(
+            "Message on {} kept as dead letter {}",
+            topic, dead_letter.id
+        );
+        Ok(())
+    }
+
+    pub async fn get_dead_letters(
+        &self,
+        req: DeadLetterListRequest,
+    ) -> Result<Vec<DeadLetterResponse>, ServiceError> {
+        let dead_letters = self
+            .repository
+            .get_dead_letters(&DeadLetterFilter {
+                topic: req.topic,
+                replayed: req.replayed,
+                limit: req.limit.unwrap_or(DEFAULT_DEAD_LETTER_LIMIT),
+            })
+            .await?;
+
+        Ok(dead_letters
+            .into_iter()
+            .map(DeadLetterResponse::from)
+            .collect())
+    }
+
+    pub async fn get_dead_letter(&self, id: &i64) -> Result<DeadLetterResponse, ServiceError> {
+        self.repository
+            .get_dead_letter(id)
+            .await?
+            .map(DeadLetterResponse::from)
+            .ok_or_else(|| ServiceError::NotFound(format!("Dead letter {} not found", id)))
+    }
+
+    /// Process a dead letter again, as if it had just been received. A failed replay
+    /// keeps the dead letter pending with the new failure reason.
+    pub async fn replay(&self, id: &i64) -> Result<DeadLetterResponse, ServiceError> {
+        let Some(dead_letter) = self.repository.claim_replay(id).await? else {
+            return Err(match self.repository.get_dead_letter(id).await? {
+                Some(_) => ServiceError::Conflict(format!(
+                    "Dead letter {} was already replayed or is being replayed",
+                    id
+                )),
+                None => ServiceError::NotFound(format!("Dead letter {} not found", id)),
+            });
+        };
+
+        let dead_letter = match self
+            .handler
+            .handle(
+                &dead_letter.topic,
+                &dead_letter.payload,
+                dead_letter.received_at,
+            )
+            .await
+        {
+            Ok(()) => dead_letter,
+            Err(e) => {
+                self.repository
+                    .record_failed_replay(id, &e.to_string())
+                    .await?
+            }
+        };
+
+        Ok(dead_letter.into())
+    }
+
+    /// Replay pending dead letters in the order they were received. Dead letters claimed
+    /// by a concurrent replay in the meantime are skipped.
+    pub async fn replay_pending(
+        &self,
+        req: DeadLetterReplayRequest,
+    ) -> Result<DeadLetterReplayResponse, ServiceError> {
+        let dead_letters = self
+            .repository
+            .get_pending_dead_letters(
+                req.topic.as_deref(),
+                req.limit.unwrap_or(DEFAULT_DEAD_LETTER_LIMIT),
+            )
+            .await?;
+
+        let mut result = DeadLetterReplayResponse {
+            replayed: 0,
+            failed: 0,
+        };
+        for pending in dead_letters {
+            let Some(dead_letter) = self.repository.claim_replay(&pending.id).await? else {
+                continue;
+            };
+            match self
+                .handler
+                .handle(
+                    &dead_letter.topic,
+                    &dead_letter.payload,
+                    dead_letter.received_at,
+                )
+                .await
+            {
+                Ok(()) => result.replayed += 1,
+                Err(e) => {
+                    self.repository
+                        .record_failed_replay(&dead_letter.id, &e.to_string())
+                        .await?;
+                    result.failed += 1;
+                }
+            }
+        }
+
+        Ok(result)
+    }
+}
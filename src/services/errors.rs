@@ -81,3 +81,16 @@ impl From<crate::repository::errors::RepositoryError> for ServiceError {
         }
     }
 }
+
+impl From<crate::tracking::errors::TrackingError> for ServiceError {
+    fn from(err: crate::tracking::errors::TrackingError) -> Self {
+        match err {
+            crate::tracking::errors::TrackingError::InvalidTle(_) => {
+                ServiceError::BadRequest(err.to_string())
+            }
+            crate::tracking::errors::TrackingError::Propagation(_) => {
+                ServiceError::Internal(err.to_string())
+            }
+        }
+    }
+}
@@ -1,13 +1,19 @@
-use crate::models::entities::GroundStation;
+use crate::config::GroundStationsConfig;
+use crate::models::entities::{GroundStation, StationHeartbeat};
+use crate::models::responses::{GroundStationResponse, GroundStationStatusResponse, StationStatus};
 use crate::repository::ground_station::GroundStationRepository;
+use crate::services::errors::ServiceError;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
 
 pub struct GroundStationService {
     repository: GroundStationRepository,
+    config: GroundStationsConfig,
 }
 
 impl GroundStationService {
-    pub fn new(repository: GroundStationRepository) -> Self {
-        Self { repository }
+    pub fn new(repository: GroundStationRepository, config: GroundStationsConfig) -> Self {
+        Self { repository, config }
     }
 
     pub async fn create_ground_station(
@@ -17,10 +23,30 @@ impl GroundStationService {
         self.repository.create_ground_station(ground_station).await
     }
 
+    /// Get every ground station with whether it is online
     pub async fn get_all_ground_stations(
         &self,
-    ) -> Result<Vec<GroundStation>, Box<dyn std::error::Error + Send + Sync>> {
-        self.repository.get_all_ground_stations().await
+    ) -> Result<Vec<GroundStationResponse>, Box<dyn std::error::Error + Send + Sync>> {
+        let last_heartbeats: HashMap<i64, DateTime<Utc>> = self
+            .repository
+            .get_heartbeats()
+            .await?
+            .into_iter()
+            .map(|heartbeat| (heartbeat.gs_id, heartbeat.received_at))
+            .collect();
+
+        let gss = self.repository.get_all_ground_stations().await?;
+        Ok(gss
+            .into_iter()
+            .map(|gs| {
+                let last_heartbeat = last_heartbeats.get(&gs.id).copied();
+                GroundStationResponse::new(
+                    gs,
+                    self.status(last_heartbeat),
+                    last_heartbeat.map(|t| t.timestamp()),
+                )
+            })
+            .collect())
     }
 
     pub async fn get_ground_station(
@@ -37,4 +63,32 @@ impl GroundStationService {
     ) -> Result<Option<()>, Box<dyn std::error::Error + Send + Sync>> {
         self.repository.set_tle_for_ground_station(id, tle).await
     }
+
+    /// Keep a heartbeat as the station's latest state
+    pub async fn record_heartbeat(&self, heartbeat: &StationHeartbeat) -> Result<(), ServiceError> {
+        Ok(self.repository.save_heartbeat(heartbeat).await?)
+    }
+
+    /// Whether a station is online, with the state reported in its latest heartbeat
+    pub async fn get_status(&self, id: &i64) -> Result<GroundStationStatusResponse, ServiceError> {
+        if self.repository.get_ground_station(id).await?.is_none() {
+            return Err(ServiceError::NotFound(format!(
+                "Ground station {} not found",
+                id
+            )));
+        }
+
+        let heartbeat = self.repository.get_heartbeat(id).await?;
+        let status = self.status(heartbeat.as_ref().map(|h| h.received_at));
+        Ok(GroundStationStatusResponse::new(*id, status, heartbeat))
+    }
+
+    /// A station is online while its latest heartbeat is within the offline timeout
+    fn status(&self, last_heartbeat: Option<DateTime<Utc>>) -> StationStatus {
+        let timeout = Duration::seconds(self.config.offline_timeout_secs as i64);
+        match last_heartbeat {
+            Some(received_at) if Utc::now() - received_at <= timeout => StationStatus::Online,
+            _ => StationStatus::Offline,
+        }
+    }
 }
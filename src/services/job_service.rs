@@ -1,24 +1,363 @@
-use crate::models::entities::Job;
-use crate::repository::job::JobRepository;
+use crate::config::JobsConfig;
+use crate::models::{
+    commands::{JobMessage, JobSatellite, JOB_MESSAGE_VERSION},
+    entities::{Job, JobStatus, JobStatusUpdate, Satellite},
+    requests::JobListRequest,
+    responses::{JobPageResponse, JobStatusResponse, JobStatusUpdateResponse},
+};
+use crate::repository::{
+    ground_station::GroundStationRepository,
+    job::JobRepository,
+    job_status_update::{JobStatusUpdateRepository, StatusOutcome},
+    satellite::SatelliteRepository,
+};
+use crate::services::{
+    cursor::{decode_cursor, encode_cursor},
+    errors::ServiceError,
+    message_service::MessageService,
+};
+use crate::tracking::{
+    passes::{predict_passes, Pass},
+    propagator::{Observer, Propagator},
+};
+use chrono::{DateTime, Duration, Utc};
+use log::error;
+use std::sync::Arc;
+
+const DEFAULT_PAGE_SIZE: i64 = 100;
+
+/// Slack allowed between a client-supplied AOS and the predicted one
+const AOS_TOLERANCE_SECONDS: i64 = 60;
 
 pub struct JobService {
     repository: JobRepository,
+    status_repository: JobStatusUpdateRepository,
+    satellite_repository: SatelliteRepository,
+    ground_station_repository: GroundStationRepository,
+    message_service: Arc<MessageService>,
+    config: JobsConfig,
 }
 
 impl JobService {
-    pub fn new(repository: JobRepository) -> Self {
-        Self { repository }
+    pub fn new(
+        repository: JobRepository,
+        status_repository: JobStatusUpdateRepository,
+        satellite_repository: SatelliteRepository,
+        ground_station_repository: GroundStationRepository,
+        message_service: Arc<MessageService>,
+        config: JobsConfig,
+    ) -> Self {
+        Self {
+            repository,
+            status_repository,
+            satellite_repository,
+            ground_station_repository,
+            message_service,
+            config,
+        }
     }
 
+    /// Create a job scheduled on a pass of the satellite over the ground station
+    /// and dispatch it to the station.
+    ///
+    /// When `aos` is given the job is placed on the pass containing that instant,
+    /// otherwise on the next pass that has not started yet.
     pub async fn create_job(
         &self,
         gs_id: &i64,
         sat_id: &i64,
         commands: &Vec<String>,
-    ) -> Result<Job, Box<dyn std::error::Error + Send + Sync>> {
-        // TODO: calculate start - end
-        let job = Job::new(gs_id, sat_id, commands);
-        self.repository.create_job(&job).await?;
+        aos: Option<i64>,
+    ) -> Result<Job, ServiceError> {
+        let satellite = self
+            .satellite_repository
+            .get_satellite(sat_id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("Satellite {} not found", sat_id)))?;
+        let gs = self
+            .ground_station_repository
+            .get_ground_station(gs_id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("Ground station {} not found", gs_id)))?;
+
+        let propagator = Propagator::from_tle(&satellite.tle)?;
+        let observer = Observer::new(gs.latitude, gs.longitude, gs.altitude as f64);
+
+        let now = Utc::now();
+        let horizon = now + Duration::hours(self.config.pass_horizon_hours as i64);
+        // A pass still in progress at the horizon is cut off there, so its LOS is not
+        // the real one and the job would end too early
+        let passes: Vec<Pass> = predict_passes(
+            &propagator,
+            &observer,
+            now,
+            horizon,
+            self.config.min_elevation,
+        )?
+        .into_iter()
+        .filter(|p| p.los < horizon)
+        .collect();
+
+        let pass = match aos {
+            Some(aos) => {
+                let requested = DateTime::from_timestamp(aos, 0)
+                    .ok_or_else(|| ServiceError::BadRequest("Invalid AOS timestamp".into()))?;
+                find_pass_at(&passes, requested).ok_or_else(|| {
+                    ServiceError::BadRequest(format!(
+                        "No pass of satellite {} over ground station {} at {} within the next {} hours",
+                        sat_id, gs_id, requested, self.config.pass_horizon_hours
+                    ))
+                })?
+            }
+            // A pass clipped to `now` is already in progress and too late to schedule
+            None => passes.iter().find(|p| p.aos > now).ok_or_else(|| {
+                ServiceError::BadRequest(format!(
+                    "No pass of satellite {} over ground station {} within the next {} hours",
+                    sat_id, gs_id, self.config.pass_horizon_hours
+                ))
+            })?,
+        };
+
+        let mut job = Job::new(
+            gs_id,
+            sat_id,
+            pass.aos.timestamp(),
+            pass.los.timestamp(),
+            commands,
+        );
+        job.id = self.repository.create_job(&job).await?;
+
+        // A job the station never received is removed, so a retry does not leave a
+        // second copy behind
+        if let Err(e) = self.dispatch_job(&job, &satellite).await {
+            if let Err(delete_error) = self.repository.delete_job(&job.id).await {
+                error!(
+                    "Error deleting job {} after a failed dispatch: {}",
+                    job.id, delete_error
+                );
+            }
+            return Err(e);
+        }
+
         Ok(job)
     }
+
+    /// Record the job as sent and publish its description to the ground station. The
+    /// status is recorded first, as the station may report progress right after the
+    /// publish.
+    async fn dispatch_job(&self, job: &Job, satellite: &Satellite) -> Result<(), ServiceError> {
+        let message = JobMessage {
+            version: JOB_MESSAGE_VERSION,
+            job_id: job.id,
+            gs_id: job.gs_id,
+            start: job.start_time,
+            end: job.end_time,
+            satellite: JobSatellite {
+                id: satellite.id,
+                name: satellite.name.clone(),
+                tle: satellite.tle.clone(),
+                downlink_frequency: satellite.downlink_frequency,
+                uplink_frequency: satellite.uplink_frequency,
+            },
+            commands: job.commands.clone(),
+        };
+        let payload = serde_json::to_string(&message)
+            .map_err(|e| ServiceError::Internal(format!("Failed to serialize job: {}", e)))?;
+
+        self.record_status_update(&JobStatusUpdate::new(job.id, Utc::now(), JobStatus::Sent))
+            .await?;
+
+        let topic = self.message_service.topics.jobs(job.gs_id);
+        self.message_service
+            .send_message(&topic, &payload)
+            .map_err(|e| {
+                ServiceError::Internal(format!("Job {} could not be dispatched: {}", job.id, e))
+            })
+    }
+
+    /// Record a status update for a job.
+    ///
+    /// Updates that the job state machine does not allow are still appended to the
+    /// history, together with the reason they were rejected, and reported as a conflict.
+    pub async fn record_status_update(&self, update: &JobStatusUpdate) -> Result<(), ServiceError> {
+        match self
+            .status_repository
+            .record_status_update(update, check_transition)
+            .await?
+        {
+            StatusOutcome::Accepted => Ok(()),
+            StatusOutcome::Rejected(reason) => Err(ServiceError::Conflict(format!(
+                "Job {}: {}",
+                update.job_id, reason
+            ))),
+            StatusOutcome::JobNotFound => Err(ServiceError::NotFound(format!(
+                "Job {} not found",
+                update.job_id
+            ))),
+        }
+    }
+
+    /// Get a page of jobs, most recent first, continuing from the request's cursor
+    pub async fn get_jobs_page(
+        &self,
+        req: JobListRequest,
+    ) -> Result<JobPageResponse, ServiceError> {
+        let page_size = req.page_size.unwrap_or(DEFAULT_PAGE_SIZE);
+        let after = req.cursor.as_deref().map(decode_cursor).transpose()?;
+
+        // One extra row tells whether another page follows
+        let mut items = self.repository.get_jobs_page(after, page_size + 1).await?;
+        let next_cursor = if items.len() as i64 > page_size {
+            items.truncate(page_size as usize);
+            items.last().and_then(|job| {
+                DateTime::from_timestamp(job.start_time, 0)
+                    .map(|start| encode_cursor(start, job.id))
+            })
+        } else {
+            None
+        };
+
+        Ok(JobPageResponse { items, next_cursor })
+    }
+
+    /// Get one job by ID
+    pub async fn get_job(&self, id: &i64) -> Result<Option<Job>, ServiceError> {
+        Ok(self.repository.get_job(id).await?)
+    }
+
+    /// Get the latest status of a job along with its full status history
+    pub async fn get_job_status(&self, id: &i64) -> Result<JobStatusResponse, ServiceError> {
+        if self.repository.get_job(id).await?.is_none() {
+            return Err(ServiceError::NotFound(format!("Job {} not found", id)));
+        }
+
+        let latest = self.status_repository.get_latest_status(id).await?;
+        let history = self.status_repository.get_job_status_updates(id).await?;
+
+        Ok(JobStatusResponse {
+            job_id: *id,
+            status: latest.as_ref().map(|update| update.status),
+            updated_at: latest.as_ref().map(|update| update.timestamp.timestamp()),
+            history: history
+                .into_iter()
+                .map(JobStatusUpdateResponse::from)
+                .collect(),
+        })
+    }
+}
+
+/// Find the predicted pass that contains the requested instant
+fn find_pass_at(passes: &[Pass], requested: DateTime<Utc>) -> Option<&Pass> {
+    let tolerance = Duration::seconds(AOS_TOLERANCE_SECONDS);
+    passes
+        .iter()
+        .find(|p| p.aos - tolerance <= requested && requested < p.los)
+}
+
+/// Job state machine.
+///
+/// Jobs move forward through `Sent -> Received -> Started -> Completed`. Steps may be
+/// skipped, since a station's intermediate reports can be lost, but never repeated or
+/// reversed. `Error` can be reached from any non-final state. `Completed` and `Error`
+/// are final.
+fn check_transition(current: Option<JobStatus>, next: JobStatus) -> Result<(), String> {
+    fn rank(status: JobStatus) -> u8 {
+        match status {
+            JobStatus::Sent => 0,
+            JobStatus::Received => 1,
+            JobStatus::Started => 2,
+            JobStatus::Completed => 3,
+            JobStatus::Error => 4,
+        }
+    }
+
+    match current {
+        None if next == JobStatus::Sent => Ok(()),
+        None => Err(format!("job has not been sent, cannot become {:?}", next)),
+        Some(current) if current == next => Err(format!("duplicate {:?} update", next)),
+        Some(current @ (JobStatus::Completed | JobStatus::Error)) => Err(format!(
+            "job is already {:?}, cannot become {:?}",
+            current, next
+        )),
+        Some(_) if next == JobStatus::Error => Ok(()),
+        Some(current) if rank(next) > rank(current) => Ok(()),
+        Some(current) => Err(format!("illegal transition {:?} -> {:?}", current, next)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use JobStatus::*;
+
+    #[test]
+    fn first_update_must_be_sent() {
+        assert_eq!(check_transition(None, Sent), Ok(()));
+        for next in [Received, Started, Completed, Error] {
+            assert!(check_transition(None, next).is_err(), "None -> {:?}", next);
+        }
+    }
+
+    #[test]
+    fn allows_forward_moves() {
+        let moves = [
+            (Sent, Received),
+            (Sent, Started),
+            (Sent, Completed),
+            (Received, Started),
+            (Received, Completed),
+            (Started, Completed),
+        ];
+        for (current, next) in moves {
+            assert_eq!(
+                check_transition(Some(current), next),
+                Ok(()),
+                "{:?} -> {:?}",
+                current,
+                next
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_backward_moves() {
+        let moves = [(Received, Sent), (Started, Sent), (Started, Received)];
+        for (current, next) in moves {
+            let error = check_transition(Some(current), next).unwrap_err();
+            assert!(error.contains("illegal transition"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn rejects_duplicates() {
+        for status in [Sent, Received, Started, Completed, Error] {
+            let error = check_transition(Some(status), status).unwrap_err();
+            assert!(error.contains("duplicate"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn allows_error_from_non_final_states() {
+        for current in [Sent, Received, Started] {
+            assert_eq!(
+                check_transition(Some(current), Error),
+                Ok(()),
+                "{:?}",
+                current
+            );
+        }
+    }
+
+    #[test]
+    fn final_states_cannot_change() {
+        for current in [Completed, Error] {
+            for next in [Sent, Received, Started, Completed, Error] {
+                if next == current {
+                    continue;
+                }
+                let error = check_transition(Some(current), next).unwrap_err();
+                assert!(error.contains("already"), "{}", error);
+            }
+        }
+    }
 }
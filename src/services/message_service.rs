@@ -1,16 +1,36 @@
-use crate::messaging::broker::MqttBroker;
+use crate::messaging::{
+    broker::{MqttBroker, Presence, PublishError},
+    connection::ConnectionStatus,
+    topics::TopicScheme,
+};
 use rumqttc::ClientError;
+use std::sync::Arc;
 
 pub struct MessageService {
     pub message_broker: MqttBroker,
+    pub topics: Arc<TopicScheme>,
 }
 
 impl MessageService {
-    pub fn new(message_broker: MqttBroker) -> Self {
-        Self { message_broker }
+    pub fn new(message_broker: MqttBroker, topics: Arc<TopicScheme>) -> Self {
+        Self {
+            message_broker,
+            topics,
+        }
     }
 
-    pub async fn send_message(&self, topic: &str, payload: &str) -> Result<(), ClientError> {
-        self.message_broker.publish(topic, payload).await
+    pub fn connection_status(&self) -> ConnectionStatus {
+        self.message_broker.connection().status()
+    }
+
+    /// Tell ground stations the API is going away, before disconnecting cleanly. A clean
+    /// disconnect does not trigger the last-will message.
+    pub fn announce_offline(&self) -> Result<(), ClientError> {
+        self.message_broker
+            .publish_presence(self.topics.presence(), Presence::Offline)
+    }
+
+    pub fn send_message(&self, topic: &str, payload: &str) -> Result<(), PublishError> {
+        self.message_broker.publish(topic, payload)
     }
 }
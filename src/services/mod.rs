@@ -1,6 +1,10 @@
+pub mod alarm_service;
+pub mod cursor;
+pub mod dead_letter_service;
 pub mod errors;
 pub mod ground_station_service;
 pub mod job_service;
 pub mod message_service;
 pub mod satellite_service;
 pub mod telemetry_service;
+pub mod tracking_service;
@@ -1,5 +1,6 @@
 use crate::{
-    models::entities::Satellite, repository::satellite::SatelliteRepository,
+    models::entities::{CcsdsConfig, Satellite},
+    repository::satellite::SatelliteRepository,
     services::errors::ServiceError,
 };
 
@@ -92,6 +93,41 @@ impl SatelliteService {
         }
     }
 
+    /// Get the CCSDS framing settings of a satellite
+    pub async fn get_ccsds_config(&self, id: &i64) -> Result<CcsdsConfig, ServiceError> {
+        self.repository
+            .get_satellite(id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("Satellite with ID {} not found", id)))?;
+
+        self.repository.get_ccsds_config(id).await?.ok_or_else(|| {
+            ServiceError::NotFound(format!("Satellite {} does not use CCSDS framing", id))
+        })
+    }
+
+    /// Set the CCSDS framing settings of a satellite
+    pub async fn set_ccsds_config(
+        &self,
+        id: &i64,
+        config: CcsdsConfig,
+    ) -> Result<CcsdsConfig, ServiceError> {
+        // The spacecraft identifier is a 10-bit field of the frame header
+        if !(0..=1023).contains(&config.spacecraft_id) {
+            return Err(ServiceError::BadRequest(
+                "Spacecraft ID must be between 0 and 1023".into(),
+            ));
+        }
+
+        if self.repository.set_ccsds_config(id, &config).await? {
+            Ok(config)
+        } else {
+            Err(ServiceError::NotFound(format!(
+                "Satellite with ID {} not found",
+                id
+            )))
+        }
+    }
+
     /// Delete a satellite by ID
     pub async fn delete_satellite(&self, id: &i64) -> Result<bool, ServiceError> {
         let result = self.repository.delete_satellite(id).await?;
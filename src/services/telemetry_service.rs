@@ -1,69 +1,903 @@
-use crate::models::responses::TelemetryResponse;
-use crate::repository::telemetry::TelemetryRepository;
-use rustar_types::telemetry::TelemetryRecord;
+use crate::config::TelemetryConfig;
+use crate::decoding::decoder::{decode, validate};
+use crate::framing::ccsds::{self, SpacePacket, TmFrame, VirtualChannel};
+use crate::messaging::parser::{parse_key_values, parse_telemetry};
+use crate::models::{
+    entities::{Satellite, TelemetryDecoder, TelemetryFrame, TelemetryReception, TransferFrame},
+    requests::{
+        ExportFormat, RawEncoding, SortOrder, TelemetryAggregateRequest, TelemetryExportRequest,
+        TelemetryPageRequest,
+    },
+    responses::{
+        ParameterAggregate, TelemetryAggregateResponse, TelemetryBucket, TelemetryPageResponse,
+        TelemetryParameter, TelemetryReceptionResponse, TelemetryResponse,
+    },
+};
+use crate::repository::satellite::SatelliteRepository;
+use crate::repository::telemetry::{
+    FrameToSave, SaveOutcome, TelemetryDb, TelemetryPageQuery, TelemetryRepository,
+};
+use crate::repository::telemetry_decoder::TelemetryDecoderRepository;
+use crate::services::cursor::{decode_cursor, encode_cursor};
+use crate::services::errors::ServiceError;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chrono::{DateTime, Duration, Utc};
+use futures::TryStreamExt;
+use log::{debug, info, warn};
+use sha2::{Digest, Sha256};
+use std::collections::{hash_map::Entry, BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{broadcast, mpsc};
+
+const DEFAULT_PAGE_SIZE: i64 = 100;
+
+/// Longest range a single aggregation may cover, since every row in it is decoded
+const MAX_AGGREGATE_RANGE_DAYS: i64 = 31;
+/// Most buckets a single aggregation may return
+const MAX_AGGREGATE_BUCKETS: i64 = 10_000;
+
+/// Rows buffered between the database stream and a slow export client
+const EXPORT_BUFFER: usize = 64;
+
+/// Samples buffered for live subscribers before the slowest ones start missing samples
+const LIVE_BUFFER: usize = 256;
+
+/// Highest APID a decoder can be attached to; 0x7FF is reserved for idle packets
+const MAX_APID: i32 = 0x7FE;
+
+/// Parameters of legacy key:value frames and their units
+const LEGACY_PARAMETERS: [(&str, &str); 4] = [
+    ("temperature", "degC"),
+    ("voltage", "V"),
+    ("current", "A"),
+    ("battery_level", "%"),
+];
 
 pub struct TelemetryService {
     repository: TelemetryRepository,
+    satellite_repository: SatelliteRepository,
+    decoder_repository: TelemetryDecoderRepository,
+    live: broadcast::Sender<TelemetryResponse>,
+    /// Packet reassembly state of each satellite's CCSDS virtual channels, by satellite,
+    /// receiving station and virtual channel. Stations lag each other, so sharing the
+    /// state would look like frame count gaps; their copies of a packet are merged when
+    /// it is stored.
+    channels: Mutex<HashMap<(i64, i64, u8), VirtualChannel>>,
+    config: TelemetryConfig,
 }
 
 impl TelemetryService {
-    pub fn new(repository: TelemetryRepository) -> Self {
-        Self { repository }
+    pub fn new(
+        repository: TelemetryRepository,
+        satellite_repository: SatelliteRepository,
+        decoder_repository: TelemetryDecoderRepository,
+        config: TelemetryConfig,
+    ) -> Self {
+        let (live, _) = broadcast::channel(LIVE_BUFFER);
+        Self {
+            repository,
+            satellite_repository,
+            decoder_repository,
+            live,
+            channels: Mutex::new(HashMap::new()),
+            config,
+        }
     }
 
     pub async fn get_latest_telemetry(
         &self,
-        sat_name: String,
+        satellite: String,
         limit: i32,
-    ) -> Result<Vec<TelemetryResponse>, Box<dyn std::error::Error + Send + Sync>> {
-        let records = self.repository.get_latest(sat_name, limit).await?;
-        let responses = records
-            .into_iter()
-            .map(|record| TelemetryResponse {
-                timestamp: record.timestamp,
-                temperature: record.temperature,
-                voltage: record.voltage,
-                current: record.current,
-                battery_level: record.battery_level,
-            })
-            .collect();
+        raw: Option<RawEncoding>,
+    ) -> Result<Vec<TelemetryResponse>, ServiceError> {
+        let sat = self.resolve_satellite(&satellite).await?;
+        let decoders = self.get_decoders(&sat.id).await?;
+        let records = self.repository.get_latest(sat.id, limit).await?;
 
-        Ok(responses)
+        Ok(decode_records(records, &decoders, raw))
     }
 
     pub async fn get_historic_telemetry(
         &self,
-        sat_name: String,
+        satellite: String,
         start_time: Option<i64>,
         end_time: Option<i64>,
-    ) -> Result<Vec<TelemetryResponse>, Box<dyn std::error::Error + Send + Sync>> {
-        let records = self
-            .repository
-            .get_historic(sat_name, start_time, end_time)
-            .await?;
-        let responses = records
+        raw: Option<RawEncoding>,
+    ) -> Result<Vec<TelemetryResponse>, ServiceError> {
+        let (start, end) = parse_range(start_time, end_time)?;
+        let sat = self.resolve_satellite(&satellite).await?;
+        let decoders = self.get_decoders(&sat.id).await?;
+        let records = self.repository.get_historic(sat.id, start, end).await?;
+
+        Ok(decode_records(records, &decoders, raw))
+    }
+
+    /// Fetch a page of telemetry, continuing from the request's cursor. When parameter
+    /// names are given, samples carrying none of them are left out of the page.
+    pub async fn get_telemetry_page(
+        &self,
+        satellite: String,
+        req: TelemetryPageRequest,
+    ) -> Result<TelemetryPageResponse, ServiceError> {
+        let page_size = req.page_size.unwrap_or(DEFAULT_PAGE_SIZE);
+        let (start, end) = parse_range(req.start_time, req.end_time)?;
+        let mut after = req.cursor.as_deref().map(decode_cursor).transpose()?;
+
+        let sat = self.resolve_satellite(&satellite).await?;
+        let decoders = self.get_decoders(&sat.id).await?;
+        let names = req.parameters.as_deref().map(parse_names);
+
+        // Samples are decoded and filtered here, so rows are read in chunks until the page
+        // is full. A row left over once it is full tells that another page follows.
+        let mut items = Vec::new();
+        let next_cursor = loop {
+            let records = self
+                .repository
+                .get_page(&TelemetryPageQuery {
+                    sat_id: sat.id,
+                    gs_id: req.gs_id,
+                    start,
+                    end,
+                    after,
+                    ascending: matches!(req.order.unwrap_or_default(), SortOrder::Asc),
+                    limit: page_size + 1,
+                })
+                .await?;
+            let exhausted = (records.len() as i64) <= page_size;
+
+            let mut more = false;
+            for record in records {
+                if items.len() as i64 == page_size {
+                    more = true;
+                    break;
+                }
+                after = Some((record.timestamp, record.id));
+                let Some(mut item) = decode_record(record, &decoders, req.raw) else {
+                    continue;
+                };
+                if let Some(names) = &names {
+                    item.parameters
+                        .retain(|name, _| names.contains(name.as_str()));
+                    if item.parameters.is_empty() {
+                        continue;
+                    }
+                }
+                items.push(item);
+            }
+
+            if more {
+                break after.map(|(timestamp, id)| encode_cursor(timestamp, id));
+            }
+            if exhausted {
+                break None;
+            }
+        };
+
+        Ok(TelemetryPageResponse { items, next_cursor })
+    }
+
+    /// Bucket a satellite's decoded telemetry by time and summarize every valid
+    /// parameter value per bucket. Rows are streamed, so long ranges stay cheap on memory,
+    /// but each is decoded, so the range and bucket count are capped.
+    pub async fn aggregate_telemetry(
+        &self,
+        satellite: String,
+        req: TelemetryAggregateRequest,
+    ) -> Result<TelemetryAggregateResponse, ServiceError> {
+        let (start, end) = aggregate_range(req.start_time, req.end_time, req.interval)?;
+        let names = req.parameters.as_deref().map(parse_names);
+
+        let sat = self.resolve_satellite(&satellite).await?;
+        let decoders = self.get_decoders(&sat.id).await?;
+
+        let mut buckets: BTreeMap<i64, BTreeMap<String, ParameterStats>> = BTreeMap::new();
+        let mut rows = self.repository.stream_range(sat.id, req.gs_id, start, end);
+        while let Some(record) = rows.try_next().await? {
+            let Some(sample) = decode_record(record, &decoders, None) else {
+                continue;
+            };
+            let bucket_start = sample.timestamp - sample.timestamp.rem_euclid(req.interval);
+
+            for (name, param) in sample.parameters {
+                if names.as_ref().is_some_and(|n| !n.contains(name.as_str())) {
+                    continue;
+                }
+                let Some(value) = param.value.filter(|_| param.valid) else {
+                    continue;
+                };
+                buckets
+                    .entry(bucket_start)
+                    .or_default()
+                    .entry(name)
+                    .or_insert_with(|| ParameterStats::new(param.unit))
+                    .add(value);
+            }
+        }
+
+        Ok(TelemetryAggregateResponse {
+            sat_id: sat.id,
+            interval: req.interval,
+            buckets: buckets
+                .into_iter()
+                .map(|(bucket_start, stats)| TelemetryBucket {
+                    start: bucket_start,
+                    end: bucket_start + req.interval,
+                    parameters: stats
+                        .into_iter()
+                        .map(|(name, stats)| (name, stats.finish()))
+                        .collect(),
+                })
+                .collect(),
+        })
+    }
+
+    /// Export a satellite's telemetry as CSV or NDJSON lines. Rows are read from the
+    /// database by a background task and handed over through a bounded channel, so
+    /// the export never holds more than a few rows in memory. The task stops as soon
+    /// as the receiver is dropped.
+    pub async fn export_telemetry(
+        self: &Arc<Self>,
+        satellite: String,
+        req: TelemetryExportRequest,
+        format: ExportFormat,
+    ) -> Result<mpsc::Receiver<Result<String, ServiceError>>, ServiceError> {
+        let (start, end) = parse_range(req.start_time, req.end_time)?;
+        let names: Option<HashSet<String>> = req
+            .parameters
+            .as_deref()
+            .map(|names| parse_names(names).into_iter().map(String::from).collect());
+
+        let sat = self.resolve_satellite(&satellite).await?;
+        let decoders = self.get_decoders(&sat.id).await?;
+
+        let columns: Vec<String> = decoders
+            .columns()
             .into_iter()
-            .map(|record| TelemetryResponse {
-                timestamp: record.timestamp,
-                temperature: record.temperature,
-                voltage: record.voltage,
-                current: record.current,
-                battery_level: record.battery_level,
+            .filter(|name| names.as_ref().is_none_or(|n| n.contains(name)))
+            .collect();
+        let raw = req.raw.unwrap_or(RawEncoding::Hex);
+        let gs_id = req.gs_id;
+
+        let (tx, rx) = mpsc::channel(EXPORT_BUFFER);
+        let service = Arc::clone(self);
+        tokio::spawn(async move {
+            if matches!(format, ExportFormat::Csv)
+                && tx.send(Ok(csv_header(&columns))).await.is_err()
+            {
+                return;
+            }
+
+            let mut rows = service.repository.stream_range(sat.id, gs_id, start, end);
+            loop {
+                let record = match rows.try_next().await {
+                    Ok(Some(record)) => record,
+                    Ok(None) => break,
+                    Err(e) => {
+                        let _ = tx.send(Err(e.into())).await;
+                        break;
+                    }
+                };
+                let Some(mut sample) = decode_record(record, &decoders, Some(raw)) else {
+                    continue;
+                };
+                if let Some(names) = &names {
+                    sample.parameters.retain(|name, _| names.contains(name));
+                    if sample.parameters.is_empty() {
+                        continue;
+                    }
+                }
+
+                let line = match format {
+                    ExportFormat::Csv => Ok(csv_row(&sample, &columns)),
+                    ExportFormat::Ndjson => serde_json::to_string(&sample)
+                        .map(|json| json + "\n")
+                        .map_err(|e| ServiceError::Internal(e.to_string())),
+                };
+                if tx.send(line).await.is_err() {
+                    // The client went away
+                    break;
+                }
+            }
+        });
+
+        Ok(rx)
+    }
+
+    /// Store frames received by ground stations in one batch, decode the new ones and
+    /// push them to live subscribers. Returns the sample stored for each frame, or `None`
+    /// for a frame already stored from another station, of which only the reception is
+    /// recorded.
+    pub async fn save_frames(
+        &self,
+        frames: &[(TelemetryFrame, TelemetryReception)],
+    ) -> Result<Vec<Option<TelemetryResponse>>, ServiceError> {
+        // Loaded before saving, as a failure after the commit would have the batch saved
+        // again with every frame a duplicate, skipping its limit checks
+        let mut decoders = HashMap::new();
+        for (frame, _) in frames {
+            if let Entry::Vacant(entry) = decoders.entry(frame.sat_id) {
+                entry.insert(self.get_decoders(&frame.sat_id).await?);
+            }
+        }
+
+        let tolerance = Duration::seconds(self.config.duplicate_tolerance_secs as i64);
+        let hashes: Vec<_> = frames
+            .iter()
+            .map(|(frame, _)| Sha256::digest(&frame.fingerprint))
+            .collect();
+        let to_save: Vec<FrameToSave> = frames
+            .iter()
+            .zip(&hashes)
+            .map(|((frame, reception), hash)| FrameToSave {
+                frame,
+                frame_hash: hash.as_slice(),
+                reception,
             })
             .collect();
+        let outcomes = self.repository.save_batch(&to_save, tolerance).await?;
+
+        let mut samples = Vec::with_capacity(frames.len());
+        for ((frame, reception), outcome) in frames.iter().zip(outcomes) {
+            let id = match outcome {
+                SaveOutcome::Stored(id) => id,
+                SaveOutcome::Duplicate(id) => {
+                    info!(
+                        "Telemetry {} also received by ground station {}",
+                        id, reception.gs_id
+                    );
+                    samples.push(None);
+                    continue;
+                }
+            };
+
+            let parameters =
+                decode_parameters(decoders[&frame.sat_id].for_apid(frame.apid), &frame.payload)
+                    .unwrap_or_else(|e| {
+                        warn!("Stored telemetry {} could not be decoded: {}", id, e);
+                        BTreeMap::new()
+                    });
+            let sample = TelemetryResponse {
+                id,
+                timestamp: frame.timestamp.timestamp(),
+                sat_id: frame.sat_id,
+                gs_id: reception.gs_id,
+                gs_ids: vec![reception.gs_id],
+                source_callsign: frame.source_callsign.clone(),
+                destination_callsign: frame.destination_callsign.clone(),
+                apid: frame.apid,
+                sequence_count: frame.sequence_count,
+                parameters,
+                raw: None,
+            };
+
+            // Sending only fails when nobody is subscribed
+            let _ = self.live.send(sample.clone());
+            samples.push(Some(sample));
+        }
+
+        Ok(samples)
+    }
+
+    /// Extract the space packets completed by a CCSDS TM transfer frame, each as a frame
+    /// of its own, so packets heard by several stations are stored once
+    pub async fn extract_packets(
+        &self,
+        transfer_frame: &TransferFrame,
+    ) -> Result<Vec<TelemetryFrame>, ServiceError> {
+        let frame = self.parse_transfer_frame(transfer_frame).await?;
+        let packets = self
+            .channels
+            .lock()
+            .unwrap()
+            .entry((
+                transfer_frame.sat_id,
+                transfer_frame.gs_id,
+                frame.virtual_channel,
+            ))
+            .or_default()
+            .push(&frame);
+
+        Ok(packet_frames(transfer_frame, packets))
+    }
+
+    /// Extract the space packets contained in a replayed transfer frame on its own,
+    /// leaving the reassembly state of live frames alone. Packets that began in an
+    /// earlier frame cannot be rebuilt.
+    pub async fn extract_replayed_packets(
+        &self,
+        transfer_frame: &TransferFrame,
+    ) -> Result<Vec<TelemetryFrame>, ServiceError> {
+        let frame = self.parse_transfer_frame(transfer_frame).await?;
+        let packets = VirtualChannel::default().push(&frame);
+
+        Ok(packet_frames(transfer_frame, packets))
+    }
 
-        Ok(responses)
+    /// Parse a transfer frame with its satellite's CCSDS settings
+    async fn parse_transfer_frame(
+        &self,
+        transfer_frame: &TransferFrame,
+    ) -> Result<TmFrame, ServiceError> {
+        let sat_id = transfer_frame.sat_id;
+        let config = self
+            .satellite_repository
+            .get_ccsds_config(&sat_id)
+            .await?
+            .ok_or_else(|| {
+                ServiceError::BadRequest(format!("Satellite {} does not use CCSDS framing", sat_id))
+            })?;
+
+        let frame = ccsds::parse(&transfer_frame.data, config.fecf)
+            .map_err(|e| ServiceError::BadRequest(e.to_string()))?;
+        if frame.spacecraft_id as i32 != config.spacecraft_id {
+            return Err(ServiceError::BadRequest(format!(
+                "Frame of spacecraft {} received for satellite {}",
+                frame.spacecraft_id, sat_id
+            )));
+        }
+        debug!(
+            "TM frame of satellite {}: VC {}, MC count {}, VC count {}, OCF {:?}",
+            sat_id,
+            frame.virtual_channel,
+            frame.master_channel_count,
+            frame.virtual_channel_count,
+            frame.ocf
+        );
+
+        Ok(frame)
+    }
+
+    /// Every ground station reception of a stored frame
+    pub async fn get_receptions(
+        &self,
+        telemetry_id: &i64,
+    ) -> Result<Vec<TelemetryReceptionResponse>, ServiceError> {
+        let receptions = self.repository.get_receptions(telemetry_id).await?;
+        if receptions.is_empty() {
+            return Err(ServiceError::NotFound(format!(
+                "Telemetry {} not found",
+                telemetry_id
+            )));
+        }
+
+        Ok(receptions
+            .into_iter()
+            .map(TelemetryReceptionResponse::from)
+            .collect())
+    }
+
+    /// Subscribe to telemetry of a satellite as it is ingested, optionally restricted to
+    /// some of its parameters
+    pub async fn subscribe_live(
+        &self,
+        satellite: String,
+        parameters: Option<String>,
+    ) -> Result<LiveTelemetry, ServiceError> {
+        let sat = self.resolve_satellite(&satellite).await?;
+
+        Ok(LiveTelemetry {
+            sat_id: sat.id,
+            names: parameters
+                .as_deref()
+                .map(|names| parse_names(names).into_iter().map(String::from).collect()),
+            receiver: self.live.subscribe(),
+        })
+    }
+
+    /// Get the telemetry decoder configured for a satellite's APID, or its default
+    /// decoder when `apid` is `None`
+    pub async fn get_decoder(
+        &self,
+        sat_id: &i64,
+        apid: Option<i32>,
+    ) -> Result<TelemetryDecoder, ServiceError> {
+        self.satellite_repository
+            .get_satellite(sat_id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("Satellite {} not found", sat_id)))?;
+
+        self.decoder_repository
+            .get_decoder(sat_id, apid)
+            .await?
+            .ok_or_else(|| match apid {
+                Some(apid) => ServiceError::NotFound(format!(
+                    "Satellite {} has no telemetry decoder for APID {}",
+                    sat_id, apid
+                )),
+                None => {
+                    ServiceError::NotFound(format!("Satellite {} has no telemetry decoder", sat_id))
+                }
+            })
     }
 
-    pub async fn save_telemetry(
+    /// Create or replace the telemetry decoder of a satellite's APID, or its default
+    /// decoder when `apid` is `None`
+    pub async fn set_decoder(
         &self,
-        timestamp: i64,
-        temperature: f32,
-        voltage: f32,
-        current: f32,
-        battery_level: i32,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let record = TelemetryRecord::new(timestamp, temperature, voltage, current, battery_level);
-        self.repository.save(record).await
+        sat_id: &i64,
+        apid: Option<i32>,
+        decoder: TelemetryDecoder,
+    ) -> Result<TelemetryDecoder, ServiceError> {
+        validate(&decoder).map_err(ServiceError::BadRequest)?;
+        if apid.is_some_and(|apid| !(0..=MAX_APID).contains(&apid)) {
+            return Err(ServiceError::BadRequest(format!(
+                "APID must be between 0 and {}",
+                MAX_APID
+            )));
+        }
+
+        self.satellite_repository
+            .get_satellite(sat_id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("Satellite {} not found", sat_id)))?;
+
+        self.decoder_repository
+            .set_decoder(sat_id, apid, &decoder)
+            .await?;
+        Ok(decoder)
+    }
+
+    async fn get_decoders(&self, sat_id: &i64) -> Result<Decoders, ServiceError> {
+        let mut decoders = Decoders::default();
+        for (apid, decoder) in self.decoder_repository.get_decoders(sat_id).await? {
+            match apid {
+                Some(apid) => {
+                    decoders.by_apid.insert(apid, decoder);
+                }
+                None => decoders.default = Some(decoder),
+            }
+        }
+        Ok(decoders)
+    }
+
+    /// Look a satellite up by ID, falling back to its name
+    async fn resolve_satellite(&self, satellite: &str) -> Result<Satellite, ServiceError> {
+        let sat = match satellite.parse::<i64>() {
+            Ok(id) => self.satellite_repository.get_satellite(&id).await?,
+            Err(_) => {
+                self.satellite_repository
+                    .get_satellite_by_name(satellite)
+                    .await?
+            }
+        };
+
+        sat.ok_or_else(|| ServiceError::NotFound(format!("Satellite {} not found", satellite)))
+    }
+}
+
+/// Live telemetry subscription of one satellite
+pub struct LiveTelemetry {
+    sat_id: i64,
+    names: Option<HashSet<String>>,
+    receiver: broadcast::Receiver<TelemetryResponse>,
+}
+
+impl LiveTelemetry {
+    /// Wait for the next matching sample. Samples missed by a subscriber that fell
+    /// behind are skipped. Returns `None` once the service shuts down.
+    pub async fn next(&mut self) -> Option<TelemetryResponse> {
+        loop {
+            let mut sample = match self.receiver.recv().await {
+                Ok(sample) => sample,
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    warn!(
+                        "Live telemetry subscriber of satellite {} missed {} samples",
+                        self.sat_id, missed
+                    );
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            };
+            if sample.sat_id != self.sat_id {
+                continue;
+            }
+            if let Some(names) = &self.names {
+                sample.parameters.retain(|name, _| names.contains(name));
+                if sample.parameters.is_empty() {
+                    continue;
+                }
+            }
+            return Some(sample);
+        }
+    }
+}
+
+/// Decoders of a satellite: one per APID and a default one for frames of other APIDs
+/// or without an APID
+#[derive(Default)]
+struct Decoders {
+    default: Option<TelemetryDecoder>,
+    by_apid: BTreeMap<i32, TelemetryDecoder>,
+}
+
+impl Decoders {
+    fn for_apid(&self, apid: Option<i32>) -> Option<&TelemetryDecoder> {
+        apid.and_then(|apid| self.by_apid.get(&apid))
+            .or(self.default.as_ref())
+    }
+
+    /// Every parameter name the decoders produce, or the legacy parameters when the
+    /// satellite has no decoder
+    fn columns(&self) -> Vec<String> {
+        if self.default.is_none() && self.by_apid.is_empty() {
+            return LEGACY_PARAMETERS
+                .iter()
+                .map(|(name, _)| name.to_string())
+                .collect();
+        }
+
+        let mut columns: Vec<String> = Vec::new();
+        for decoder in self.default.iter().chain(self.by_apid.values()) {
+            for field in &decoder.fields {
+                if !columns.contains(&field.name) {
+                    columns.push(field.name.clone());
+                }
+            }
+        }
+        columns
+    }
+}
+
+/// Decode stored frames with the decoder of their APID, or as legacy key:value frames
+/// when the satellite has none. Frames that cannot be decoded are skipped and logged.
+fn decode_records(
+    records: Vec<TelemetryDb>,
+    decoders: &Decoders,
+    raw: Option<RawEncoding>,
+) -> Vec<TelemetryResponse> {
+    records
+        .into_iter()
+        .filter_map(|record| decode_record(record, decoders, raw))
+        .collect()
+}
+
+fn decode_record(
+    record: TelemetryDb,
+    decoders: &Decoders,
+    raw: Option<RawEncoding>,
+) -> Option<TelemetryResponse> {
+    let payload = record.payload.unwrap_or_default();
+
+    let parameters = match decode_parameters(decoders.for_apid(record.apid), &payload) {
+        Ok(parameters) => parameters,
+        Err(e) => {
+            warn!("Skipping undecodable telemetry {}: {}", record.id, e);
+            return None;
+        }
+    };
+
+    Some(TelemetryResponse {
+        id: record.id,
+        timestamp: record.timestamp.timestamp(),
+        sat_id: record.sat_id,
+        gs_id: record.gs_id,
+        gs_ids: record.gs_ids,
+        source_callsign: record.source_callsign,
+        destination_callsign: record.destination_callsign,
+        apid: record.apid,
+        sequence_count: record.sequence_count,
+        parameters,
+        raw: raw.map(|encoding| encode_raw(&payload, encoding)),
+    })
+}
+
+/// Decode a frame with the satellite's decoder, or as a legacy key:value frame
+fn decode_parameters(
+    decoder: Option<&TelemetryDecoder>,
+    payload: &[u8],
+) -> Result<BTreeMap<String, TelemetryParameter>, Box<dyn std::error::Error + Send + Sync>> {
+    match decoder {
+        Some(decoder) => Ok(decode(decoder, payload)
+            .into_iter()
+            .map(|param| {
+                (
+                    param.name,
+                    TelemetryParameter {
+                        value: param.value,
+                        unit: param.unit,
+                        valid: param.valid,
+                    },
+                )
+            })
+            .collect()),
+        None => decode_legacy(payload),
+    }
+}
+
+/// Map a legacy key:value frame onto the generic parameter shape
+fn decode_legacy(
+    payload: &[u8],
+) -> Result<BTreeMap<String, TelemetryParameter>, Box<dyn std::error::Error + Send + Sync>> {
+    let telemetry = parse_telemetry(&parse_key_values(&String::from_utf8_lossy(payload)))?;
+
+    let values = [
+        telemetry.temperature as f64,
+        telemetry.voltage as f64,
+        telemetry.current as f64,
+        telemetry.battery_level as f64,
+    ];
+
+    Ok(LEGACY_PARAMETERS
+        .iter()
+        .zip(values)
+        .map(|((name, unit), value)| {
+            (
+                name.to_string(),
+                TelemetryParameter {
+                    value: Some(value),
+                    unit: Some(unit.to_string()),
+                    valid: true,
+                },
+            )
+        })
+        .collect())
+}
+
+/// Space packets extracted from a transfer frame, as frames of its satellite
+fn packet_frames(transfer_frame: &TransferFrame, packets: Vec<SpacePacket>) -> Vec<TelemetryFrame> {
+    packets
+        .into_iter()
+        .map(|packet| TelemetryFrame {
+            sat_id: transfer_frame.sat_id,
+            timestamp: transfer_frame.timestamp,
+            payload: packet.data().to_vec(),
+            fingerprint: packet.bytes.clone(),
+            source_callsign: None,
+            destination_callsign: None,
+            apid: Some(packet.apid as i32),
+            sequence_count: Some(packet.sequence_count as i32),
+        })
+        .collect()
+}
+
+fn encode_raw(payload: &[u8], encoding: RawEncoding) -> String {
+    match encoding {
+        RawEncoding::Hex => hex::encode(payload),
+        RawEncoding::Base64 => BASE64.encode(payload),
+    }
+}
+
+fn csv_header(columns: &[String]) -> String {
+    let mut fields = vec![
+        "id".to_string(),
+        "timestamp".to_string(),
+        "sat_id".to_string(),
+        "gs_id".to_string(),
+    ];
+    fields.extend(columns.iter().map(|c| csv_field(c)));
+    fields.push("raw".to_string());
+    fields.join(",") + "\n"
+}
+
+/// Format a sample as a CSV row. Invalid or missing values are left empty.
+fn csv_row(sample: &TelemetryResponse, columns: &[String]) -> String {
+    let mut fields = vec![
+        sample.id.to_string(),
+        sample.timestamp.to_string(),
+        sample.sat_id.to_string(),
+        sample.gs_id.to_string(),
+    ];
+    fields.extend(columns.iter().map(|column| {
+        sample
+            .parameters
+            .get(column)
+            .filter(|param| param.valid)
+            .and_then(|param| param.value)
+            .map(|value| value.to_string())
+            .unwrap_or_default()
+    }));
+    fields.push(csv_field(sample.raw.as_deref().unwrap_or_default()));
+    fields.join(",") + "\n"
+}
+
+/// Quote a CSV field when it contains separators, quotes or line breaks
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Resolve an optional unix time range, defaulting to everything up to now
+fn parse_range(
+    start_time: Option<i64>,
+    end_time: Option<i64>,
+) -> Result<(DateTime<Utc>, DateTime<Utc>), ServiceError> {
+    let start = match start_time {
+        Some(ts) => DateTime::from_timestamp(ts, 0)
+            .ok_or_else(|| ServiceError::BadRequest("Invalid start time".into()))?,
+        None => DateTime::UNIX_EPOCH,
+    };
+    let end = match end_time {
+        Some(ts) => DateTime::from_timestamp(ts, 0)
+            .ok_or_else(|| ServiceError::BadRequest("Invalid end time".into()))?,
+        None => Utc::now(),
+    };
+    if start > end {
+        return Err(ServiceError::BadRequest(
+            "Start time must be before end time".into(),
+        ));
+    }
+    Ok((start, end))
+}
+
+/// Resolve the range of an aggregation. Without a start time it covers the longest
+/// allowed range up to the end time.
+fn aggregate_range(
+    start_time: Option<i64>,
+    end_time: Option<i64>,
+    interval: i64,
+) -> Result<(DateTime<Utc>, DateTime<Utc>), ServiceError> {
+    let max_range = Duration::days(MAX_AGGREGATE_RANGE_DAYS);
+    let (mut start, end) = parse_range(start_time, end_time)?;
+    if start_time.is_none() {
+        start = start.max(end - max_range);
+    }
+
+    if end - start > max_range {
+        return Err(ServiceError::BadRequest(format!(
+            "Aggregation range cannot exceed {} days",
+            MAX_AGGREGATE_RANGE_DAYS
+        )));
+    }
+    if (end - start).num_seconds() / interval + 1 > MAX_AGGREGATE_BUCKETS {
+        return Err(ServiceError::BadRequest(format!(
+            "Interval is too short for this range, which would exceed {} buckets",
+            MAX_AGGREGATE_BUCKETS
+        )));
+    }
+
+    Ok((start, end))
+}
+
+/// Split a comma-separated list of parameter names
+fn parse_names(names: &str) -> HashSet<&str> {
+    names
+        .split(',')
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+        .collect()
+}
+
+/// Running statistics of one parameter within a bucket
+struct ParameterStats {
+    min: f64,
+    max: f64,
+    sum: f64,
+    count: i64,
+    last: f64,
+    unit: Option<String>,
+}
+
+impl ParameterStats {
+    fn new(unit: Option<String>) -> Self {
+        Self {
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            sum: 0.0,
+            count: 0,
+            last: f64::NAN,
+            unit,
+        }
+    }
+
+    fn add(&mut self, value: f64) {
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.sum += value;
+        self.count += 1;
+        self.last = value;
+    }
+
+    fn finish(self) -> ParameterAggregate {
+        ParameterAggregate {
+            min: self.min,
+            max: self.max,
+            mean: self.sum / self.count as f64,
+            count: self.count,
+            last: self.last,
+            unit: self.unit,
+        }
     }
 }
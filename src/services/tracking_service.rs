@@ -0,0 +1,206 @@
+use crate::{
+    models::{
+        entities::{GroundStation, Satellite},
+        responses::PassResponse,
+    },
+    repository::{ground_station::GroundStationRepository, satellite::SatelliteRepository},
+    services::errors::ServiceError,
+    tracking::{
+        passes::predict_passes,
+        propagator::{Observer, Propagator},
+    },
+};
+use chrono::{DateTime, Duration, Utc};
+use log::warn;
+
+/// Window used when the client does not provide an end time
+const DEFAULT_WINDOW_HOURS: i64 = 24;
+/// Longest window a single request may ask for
+const MAX_WINDOW_DAYS: i64 = 7;
+
+pub struct TrackingService {
+    satellite_repository: SatelliteRepository,
+    ground_station_repository: GroundStationRepository,
+}
+
+impl TrackingService {
+    pub fn new(
+        satellite_repository: SatelliteRepository,
+        ground_station_repository: GroundStationRepository,
+    ) -> Self {
+        Self {
+            satellite_repository,
+            ground_station_repository,
+        }
+    }
+
+    /// Predict the passes of a satellite over every ground station
+    pub async fn get_satellite_passes(
+        &self,
+        sat_id: &i64,
+        start_time: Option<i64>,
+        end_time: Option<i64>,
+        min_elevation: f64,
+    ) -> Result<Vec<PassResponse>, ServiceError> {
+        let (start, end) = prediction_window(start_time, end_time)?;
+
+        let satellite = self
+            .satellite_repository
+            .get_satellite(sat_id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("Satellite {} not found", sat_id)))?;
+        let propagator = Propagator::from_tle(&satellite.tle)?;
+
+        let mut passes = Vec::new();
+        for gs in self
+            .ground_station_repository
+            .get_all_ground_stations()
+            .await?
+        {
+            passes.extend(passes_for(
+                &propagator,
+                &satellite,
+                &gs,
+                start,
+                end,
+                min_elevation,
+            )?);
+        }
+
+        passes.sort_by_key(|pass| pass.aos);
+        Ok(passes)
+    }
+
+    /// Predict the passes of every satellite over a ground station
+    pub async fn get_ground_station_passes(
+        &self,
+        gs_id: &i64,
+        start_time: Option<i64>,
+        end_time: Option<i64>,
+        min_elevation: f64,
+    ) -> Result<Vec<PassResponse>, ServiceError> {
+        let (start, end) = prediction_window(start_time, end_time)?;
+
+        let gs = self
+            .ground_station_repository
+            .get_ground_station(gs_id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("Ground station {} not found", gs_id)))?;
+
+        let mut passes = Vec::new();
+        for satellite in self.satellite_repository.get_all_satellites().await? {
+            // A single satellite with a broken TLE should not hide everyone else's passes
+            let propagator = match Propagator::from_tle(&satellite.tle) {
+                Ok(propagator) => propagator,
+                Err(e) => {
+                    warn!("Skipping satellite {}: {}", satellite.id, e);
+                    continue;
+                }
+            };
+
+            passes.extend(passes_for(
+                &propagator,
+                &satellite,
+                &gs,
+                start,
+                end,
+                min_elevation,
+            )?);
+        }
+
+        passes.sort_by_key(|pass| pass.aos);
+        Ok(passes)
+    }
+}
+
+/// Resolve and validate the requested prediction window
+fn prediction_window(
+    start_time: Option<i64>,
+    end_time: Option<i64>,
+) -> Result<(DateTime<Utc>, DateTime<Utc>), ServiceError> {
+    let start = match start_time {
+        Some(ts) => DateTime::from_timestamp(ts, 0)
+            .ok_or_else(|| ServiceError::BadRequest("Invalid start time".into()))?,
+        None => Utc::now(),
+    };
+    let end = match end_time {
+        Some(ts) => DateTime::from_timestamp(ts, 0)
+            .ok_or_else(|| ServiceError::BadRequest("Invalid end time".into()))?,
+        None => start + Duration::hours(DEFAULT_WINDOW_HOURS),
+    };
+
+    if end <= start {
+        return Err(ServiceError::BadRequest(
+            "End time must be after start time".into(),
+        ));
+    }
+
+    if end - start > Duration::days(MAX_WINDOW_DAYS) {
+        return Err(ServiceError::BadRequest(format!(
+            "Prediction window cannot exceed {} days",
+            MAX_WINDOW_DAYS
+        )));
+    }
+
+    Ok((start, end))
+}
+
+fn passes_for(
+    propagator: &Propagator,
+    satellite: &Satellite,
+    gs: &GroundStation,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    min_elevation: f64,
+) -> Result<Vec<PassResponse>, ServiceError> {
+    let observer = Observer::new(gs.latitude, gs.longitude, gs.altitude as f64);
+    let passes = predict_passes(propagator, &observer, start, end, min_elevation)?;
+
+    Ok(passes
+        .into_iter()
+        .map(|pass| PassResponse {
+            sat_id: satellite.id,
+            gs_id: gs.id,
+            aos: pass.aos.timestamp(),
+            tca: pass.tca.timestamp(),
+            los: pass.los.timestamp(),
+            max_elevation: pass.max_elevation,
+            aos_azimuth: pass.aos_azimuth,
+            los_azimuth: pass.los_azimuth,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: i64 = 1_700_000_000;
+
+    #[test]
+    fn defaults_the_end_of_the_window() {
+        let (start, end) = prediction_window(Some(START), None).unwrap();
+        assert_eq!(start.timestamp(), START);
+        assert_eq!(end - start, Duration::hours(DEFAULT_WINDOW_HOURS));
+    }
+
+    #[test]
+    fn caps_the_window_length() {
+        let longest = START + Duration::days(MAX_WINDOW_DAYS).num_seconds();
+        assert!(prediction_window(Some(START), Some(longest)).is_ok());
+        assert!(matches!(
+            prediction_window(Some(START), Some(longest + 1)),
+            Err(ServiceError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_empty_windows() {
+        for end in [START, START - 1] {
+            assert!(matches!(
+                prediction_window(Some(START), Some(end)),
+                Err(ServiceError::BadRequest(_))
+            ));
+        }
+    }
+}
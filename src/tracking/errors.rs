@@ -0,0 +1,22 @@
+use std::fmt;
+
+/// Error type for orbit propagation and pass prediction
+#[derive(Debug)]
+pub enum TrackingError {
+    /// The TLE could not be parsed or produced invalid orbital elements
+    InvalidTle(String),
+
+    /// SGP4 failed to propagate the orbit to the requested time
+    Propagation(String),
+}
+
+impl fmt::Display for TrackingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrackingError::InvalidTle(msg) => write!(f, "Invalid TLE: {}", msg),
+            TrackingError::Propagation(msg) => write!(f, "Propagation error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for TrackingError {}
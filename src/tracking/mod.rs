@@ -0,0 +1,3 @@
+pub mod errors;
+pub mod passes;
+pub mod propagator;
@@ -0,0 +1,262 @@
+use crate::tracking::errors::TrackingError;
+use crate::tracking::propagator::{Observer, Propagator};
+use chrono::{DateTime, Duration, Utc};
+
+/// Step used to scan the window for horizon crossings
+const SCAN_STEP_SECONDS: i64 = 30;
+/// Precision to which AOS, LOS and TCA are refined
+const REFINE_PRECISION_MS: i64 = 500;
+
+/// A single visibility window of a satellite over an observer
+#[derive(Debug, Clone)]
+pub struct Pass {
+    /// Acquisition of signal (satellite rises above the horizon)
+    pub aos: DateTime<Utc>,
+    /// Time of closest approach (maximum elevation)
+    pub tca: DateTime<Utc>,
+    /// Loss of signal (satellite sets below the horizon)
+    pub los: DateTime<Utc>,
+    pub max_elevation: f64,
+    pub aos_azimuth: f64,
+    pub los_azimuth: f64,
+}
+
+/// Predict the passes of a satellite over an observer within `[start, end]`.
+///
+/// AOS and LOS are taken at the geometric horizon. Passes whose maximum elevation
+/// is below `min_elevation` (degrees) are discarded. Passes already in progress at
+/// `start` or still in progress at `end` are clipped to the window.
+pub fn predict_passes(
+    propagator: &Propagator,
+    observer: &Observer,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    min_elevation: f64,
+) -> Result<Vec<Pass>, TrackingError> {
+    let elevation_at = |time: DateTime<Utc>| {
+        propagator
+            .look_angles(observer, time)
+            .map(|angles| angles.elevation)
+    };
+
+    let step = Duration::seconds(SCAN_STEP_SECONDS);
+    let mut passes = Vec::new();
+
+    let mut prev_time = start;
+    let mut prev_visible = elevation_at(start)? >= 0.0;
+    let mut aos = if prev_visible { Some(start) } else { None };
+
+    while prev_time < end {
+        let time = (prev_time + step).min(end);
+        let visible = elevation_at(time)? >= 0.0;
+
+        if !prev_visible && visible {
+            aos = Some(find_crossing(&elevation_at, prev_time, time)?);
+        } else if prev_visible && !visible {
+            if let Some(aos) = aos.take() {
+                let los = find_crossing(&elevation_at, prev_time, time)?;
+                passes.push(build_pass(propagator, observer, aos, los)?);
+            }
+        }
+
+        prev_time = time;
+        prev_visible = visible;
+    }
+
+    if let Some(aos) = aos {
+        passes.push(build_pass(propagator, observer, aos, end)?);
+    }
+
+    Ok(passes
+        .into_iter()
+        .filter(|pass| pass.max_elevation >= min_elevation)
+        .collect())
+}
+
+/// Bisect the horizon crossing between two times with opposite visibility
+fn find_crossing<F>(
+    elevation_at: &F,
+    mut low: DateTime<Utc>,
+    mut high: DateTime<Utc>,
+) -> Result<DateTime<Utc>, TrackingError>
+where
+    F: Fn(DateTime<Utc>) -> Result<f64, TrackingError>,
+{
+    let low_visible = elevation_at(low)? >= 0.0;
+
+    while (high - low).num_milliseconds() > REFINE_PRECISION_MS {
+        let mid = low + (high - low) / 2;
+        if (elevation_at(mid)? >= 0.0) == low_visible {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+
+    Ok(low + (high - low) / 2)
+}
+
+/// Locate the elevation peak between AOS and LOS and assemble the pass
+fn build_pass(
+    propagator: &Propagator,
+    observer: &Observer,
+    aos: DateTime<Utc>,
+    los: DateTime<Utc>,
+) -> Result<Pass, TrackingError> {
+    // Elevation is unimodal over a single pass, so a ternary search converges on TCA
+    let mut low = aos;
+    let mut high = los;
+    while (high - low).num_milliseconds() > REFINE_PRECISION_MS {
+        let third = (high - low) / 3;
+        let left = propagator.look_angles(observer, low + third)?.elevation;
+        let right = propagator.look_angles(observer, high - third)?.elevation;
+        if left < right {
+            low += third;
+        } else {
+            high -= third;
+        }
+    }
+
+    let tca = low + (high - low) / 2;
+    let max_elevation = propagator.look_angles(observer, tca)?.elevation;
+    let aos_azimuth = propagator.look_angles(observer, aos)?.azimuth;
+    let los_azimuth = propagator.look_angles(observer, los)?.azimuth;
+
+    Ok(Pass {
+        aos,
+        tca,
+        los,
+        max_elevation,
+        aos_azimuth,
+        los_azimuth,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const ISS_TLE: &str = "ISS (ZARYA)
+1 25544U 98067A   08264.51782528 -.00002182  00000-0 -11606-4 0  2927
+2 25544  51.6416 247.4627 0006703 130.5360 325.0288 15.72125391563537";
+
+    /// AOS and LOS may differ from the one-second reference scan by this much
+    const TOLERANCE_MS: i64 = 2_000;
+
+    fn setup() -> (Propagator, Observer, DateTime<Utc>) {
+        let propagator = Propagator::from_tle(ISS_TLE).unwrap();
+        let observer = Observer::new(52.0, 5.0, 0.0);
+        let start = Utc.with_ymd_and_hms(2008, 9, 20, 12, 0, 0).unwrap();
+        (propagator, observer, start)
+    }
+
+    /// Visibility windows found by sampling the elevation every second
+    fn reference_windows(
+        propagator: &Propagator,
+        observer: &Observer,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+        let visible = |time| propagator.look_angles(observer, time).unwrap().elevation >= 0.0;
+        let mut windows = Vec::new();
+        let mut aos = visible(start).then_some(start);
+        let mut time = start;
+        while time < end {
+            time += Duration::seconds(1);
+            match (aos, visible(time)) {
+                (None, true) => aos = Some(time),
+                (Some(rise), false) => {
+                    windows.push((rise, time));
+                    aos = None;
+                }
+                _ => {}
+            }
+        }
+        if let Some(rise) = aos {
+            windows.push((rise, end));
+        }
+        windows
+    }
+
+    fn assert_close(actual: DateTime<Utc>, expected: DateTime<Utc>) {
+        assert!(
+            (actual - expected).num_milliseconds().abs() <= TOLERANCE_MS,
+            "{} is not within {} ms of {}",
+            actual,
+            TOLERANCE_MS,
+            expected
+        );
+    }
+
+    #[test]
+    fn predicts_passes_over_a_ground_station() {
+        let (propagator, observer, start) = setup();
+        let end = start + Duration::hours(24);
+
+        let passes = predict_passes(&propagator, &observer, start, end, 0.0).unwrap();
+        let reference = reference_windows(&propagator, &observer, start, end);
+
+        // The ISS is visible from 52N a handful of times a day
+        assert!((3..=7).contains(&passes.len()), "{} passes", passes.len());
+        assert_eq!(passes.len(), reference.len());
+        for (pass, (aos, los)) in passes.iter().zip(reference) {
+            assert_close(pass.aos, aos);
+            assert_close(pass.los, los);
+            assert!(pass.aos < pass.tca && pass.tca < pass.los);
+            assert!(pass.max_elevation > 0.0 && pass.max_elevation <= 90.0);
+        }
+    }
+
+    #[test]
+    fn discards_passes_below_the_minimum_elevation() {
+        let (propagator, observer, start) = setup();
+        let end = start + Duration::hours(24);
+
+        let all = predict_passes(&propagator, &observer, start, end, 0.0).unwrap();
+        let high = predict_passes(&propagator, &observer, start, end, 30.0).unwrap();
+
+        assert!(high.iter().all(|pass| pass.max_elevation >= 30.0));
+        assert_eq!(
+            high.len(),
+            all.iter().filter(|pass| pass.max_elevation >= 30.0).count()
+        );
+    }
+
+    #[test]
+    fn clips_a_pass_in_progress_at_the_start() {
+        let (propagator, observer, start) = setup();
+        let end = start + Duration::hours(24);
+        let full = predict_passes(&propagator, &observer, start, end, 0.0).unwrap();
+        let pass = &full[1];
+        let midway = pass.aos + (pass.los - pass.aos) / 2;
+
+        let clipped = predict_passes(&propagator, &observer, midway, end, 0.0).unwrap();
+
+        assert_eq!(clipped.len(), full.len() - 1);
+        assert_eq!(clipped[0].aos, midway);
+        assert_close(clipped[0].los, pass.los);
+    }
+
+    #[test]
+    fn caps_a_pass_in_progress_at_the_end() {
+        let (propagator, observer, start) = setup();
+        let full = predict_passes(
+            &propagator,
+            &observer,
+            start,
+            start + Duration::hours(24),
+            0.0,
+        )
+        .unwrap();
+        let pass = &full[1];
+        let midway = pass.aos + (pass.los - pass.aos) / 2;
+
+        let capped = predict_passes(&propagator, &observer, start, midway, 0.0).unwrap();
+
+        assert_eq!(capped.len(), 2);
+        assert_close(capped[1].aos, pass.aos);
+        assert_eq!(capped[1].los, midway);
+        assert!(capped.iter().all(|p| p.los <= midway));
+    }
+}
@@ -0,0 +1,149 @@
+use crate::tracking::errors::TrackingError;
+use chrono::{DateTime, Utc};
+
+/// WGS84 equatorial radius in km
+const EARTH_RADIUS_KM: f64 = 6378.137;
+/// WGS84 flattening
+const EARTH_FLATTENING: f64 = 1.0 / 298.257223563;
+
+/// Geodetic position of an observer on the WGS84 ellipsoid
+#[derive(Debug, Clone, Copy)]
+pub struct Observer {
+    /// Latitude in degrees (north positive)
+    pub latitude: f64,
+    /// Longitude in degrees (east positive)
+    pub longitude: f64,
+    /// Altitude above the ellipsoid in meters
+    pub altitude: f64,
+}
+
+/// Position of a satellite as seen from an observer
+#[derive(Debug, Clone, Copy)]
+pub struct LookAngles {
+    /// Azimuth in degrees, clockwise from north
+    pub azimuth: f64,
+    /// Elevation above the local horizon in degrees
+    pub elevation: f64,
+}
+
+impl Observer {
+    pub fn new(latitude: f64, longitude: f64, altitude: f64) -> Self {
+        Self {
+            latitude,
+            longitude,
+            altitude,
+        }
+    }
+
+    /// Observer position in Earth-fixed coordinates (km)
+    fn ecef(&self) -> [f64; 3] {
+        let lat = self.latitude.to_radians();
+        let lon = self.longitude.to_radians();
+        let alt = self.altitude / 1000.0;
+        let e2 = EARTH_FLATTENING * (2.0 - EARTH_FLATTENING);
+        let n = EARTH_RADIUS_KM / (1.0 - e2 * lat.sin().powi(2)).sqrt();
+
+        [
+            (n + alt) * lat.cos() * lon.cos(),
+            (n + alt) * lat.cos() * lon.sin(),
+            (n * (1.0 - e2) + alt) * lat.sin(),
+        ]
+    }
+
+    /// Azimuth and elevation of a target given in Earth-fixed coordinates (km)
+    fn look_angles(&self, target: [f64; 3]) -> LookAngles {
+        let lat = self.latitude.to_radians();
+        let lon = self.longitude.to_radians();
+        let obs = self.ecef();
+        let (rx, ry, rz) = (target[0] - obs[0], target[1] - obs[1], target[2] - obs[2]);
+
+        // Rotate the range vector into the local east-north-up frame
+        let east = -lon.sin() * rx + lon.cos() * ry;
+        let north = -lat.sin() * lon.cos() * rx - lat.sin() * lon.sin() * ry + lat.cos() * rz;
+        let up = lat.cos() * lon.cos() * rx + lat.cos() * lon.sin() * ry + lat.sin() * rz;
+
+        let azimuth = east.atan2(north).to_degrees().rem_euclid(360.0);
+        let elevation = up.atan2((east * east + north * north).sqrt()).to_degrees();
+
+        LookAngles { azimuth, elevation }
+    }
+}
+
+/// SGP4 propagator built from a two-line element set
+pub struct Propagator {
+    elements: sgp4::Elements,
+    constants: sgp4::Constants,
+}
+
+impl Propagator {
+    /// Parse a TLE as stored in the `satellites` table.
+    ///
+    /// Accepts both the two-line form and the three-line form with a leading name line.
+    pub fn from_tle(tle: &str) -> Result<Self, TrackingError> {
+        let lines: Vec<&str> = tle
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .collect();
+
+        let line1 = lines
+            .iter()
+            .find(|l| l.starts_with("1 "))
+            .ok_or_else(|| TrackingError::InvalidTle("line 1 not found".into()))?;
+        let line2 = lines
+            .iter()
+            .find(|l| l.starts_with("2 "))
+            .ok_or_else(|| TrackingError::InvalidTle("line 2 not found".into()))?;
+        let name = lines
+            .first()
+            .filter(|l| !l.starts_with("1 ") && !l.starts_with("2 "))
+            .map(|l| l.to_string());
+
+        let elements = sgp4::Elements::from_tle(name, line1.as_bytes(), line2.as_bytes())
+            .map_err(|e| TrackingError::InvalidTle(e.to_string()))?;
+        let constants = sgp4::Constants::from_elements(&elements)
+            .map_err(|e| TrackingError::InvalidTle(e.to_string()))?;
+
+        Ok(Self {
+            elements,
+            constants,
+        })
+    }
+
+    /// Look angles from the observer to the satellite at the given time
+    pub fn look_angles(
+        &self,
+        observer: &Observer,
+        time: DateTime<Utc>,
+    ) -> Result<LookAngles, TrackingError> {
+        let minutes =
+            (time.naive_utc() - self.elements.datetime).num_milliseconds() as f64 / 60_000.0;
+        let prediction = self
+            .constants
+            .propagate(sgp4::MinutesSinceEpoch(minutes))
+            .map_err(|e| TrackingError::Propagation(e.to_string()))?;
+
+        let position = teme_to_ecef(prediction.position, gmst(time));
+        Ok(observer.look_angles(position))
+    }
+}
+
+/// Greenwich mean sidereal time in radians (IAU 1982 model)
+fn gmst(time: DateTime<Utc>) -> f64 {
+    let julian_date = time.timestamp_millis() as f64 / 86_400_000.0 + 2_440_587.5;
+    let t = (julian_date - 2_451_545.0) / 36_525.0;
+    let seconds = 67_310.548_41 + (876_600.0 * 3600.0 + 8_640_184.812_866) * t + 0.093_104 * t * t
+        - 6.2e-6 * t * t * t;
+
+    (seconds.rem_euclid(86_400.0) / 240.0).to_radians()
+}
+
+/// Rotate a TEME position into the Earth-fixed frame (polar motion is neglected)
+fn teme_to_ecef(position: [f64; 3], gmst: f64) -> [f64; 3] {
+    let (sin, cos) = gmst.sin_cos();
+    [
+        cos * position[0] + sin * position[1],
+        -sin * position[0] + cos * position[1],
+        position[2],
+    ]
+}